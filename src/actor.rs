@@ -1,15 +1,54 @@
 use crate::{
     actor_manager::ActorManager,
-    assets::{ASSETS, ArmorSlot, ConsumableEffect, ItemKind, ItemType},
-    inventory::{Equipment, Inventory},
-    map_manager::MapManager,
+    assets::{ASSETS, ActorKind, ArmorSlot, AttackEffect, CharacterClass, ConsumableEffect, DamageType, IdleBehaviour, ItemKind, ItemType, Relation, SpellKind, SpellTarget, StatusEffectKind, WeaponType},
+    attributes::Attributes,
+    dice::Dice,
+    faction::FactionRelations,
+    inventory::{Equipment, Inventory, InventoryItem},
+    map_manager::{MapManager, TileType},
     pathfinding::a_star,
     playing::Action,
     position::Position,
+    shadowcast::VISION_RADIUS,
+    status_effect::{StatModifiers, StatusEffect, StatusTick},
 };
 use crossterm::style::Color;
-use rand::seq::SliceRandom;
+use rand::{Rng, seq::IndexedRandom};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
 
+/// Slowest an actor can get, however clumsy, so turn costs stay bounded.
+const MIN_SPEED: i32 = 10;
+const HEALTH_PER_LEVEL: i32 = 10;
+const ATTRIBUTE_POINTS_PER_LEVEL: i32 = 2;
+/// Game time in action points to regain one point of mana.
+const MANA_REGEN_INTERVAL: u32 = 300;
+/// Ranged attackers try to stay at least this many tiles from their target.
+const RANGED_KEEP_DISTANCE: i64 = 3;
+/// An attack hits when a d20 plus accuracy reaches this plus the target's evasion.
+const HIT_DIFFICULTY: i32 = 8;
+/// A natural roll this high always hits and is critical, a natural 1 always misses.
+const CRITICAL_ROLL: i32 = 20;
+/// Hostiles this close wake a sleeping monster.
+const SLEEP_WAKE_DISTANCE: i64 = 2;
+/// Furthest from its post a guard will notice or chase anything.
+const GUARD_RADIUS: i64 = 6;
+/// Furthest a wandering monster heads in one go.
+const WANDER_DISTANCE: i64 = 8;
+/// How loud a noise has to be where a sleeping monster lies to wake it.
+const WAKE_VOLUME: i32 = 4;
+/// What a d20 roll has to beat to spot a hostile, before distance, darkness and stealth are added.
+const SPOT_DIFFICULTY: i32 = 6;
+/// Extra difficulty to spot someone standing in the dark.
+const DARKNESS_PENALTY: i32 = 4;
+/// Extra difficulty for a sleeper to notice someone creeping past.
+const SLEEPING_PENALTY: i32 = 6;
+/// Bonus to spot rolls for a monster that is already on the lookout.
+const SUSPICIOUS_BONUS: i32 = 4;
+/// Chance each turn that a monster with nothing going on relaxes by one level of awareness.
+const CALM_DOWN_CHANCE: f64 = 0.1;
+
+#[derive(Serialize, Deserialize)]
 pub struct Actor {
     kind_id: String,
     character_stats: Option<CharacterStats>,
@@ -17,13 +56,33 @@ pub struct Actor {
     position: Position,
     health: i32,
     ai_state: ActorAiState,
+    status_effects: Vec<StatusEffect>,
+    /// Where a guarding monster returns to once nothing is left to chase.
+    guard_post: Option<Position>,
+    awareness: Awareness,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ActorAiState {
     Idle,
     TargetingActor(usize),
     InvestigatingPosition(Position),
+    /// Running from the actor, too hurt to fight on.
+    Fleeing(usize),
+    /// Heading for a spot nearby with nothing better to do.
+    Wandering(Position),
+    Sleeping,
+    /// Standing watch over a post.
+    Guarding(Position),
+}
+
+/// How alert a monster is to hostiles around it. Only alert monsters give chase; unaware ones can be sneak attacked.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Awareness {
+    Unaware,
+    /// Has glimpsed or heard something and is going to look.
+    Suspicious,
+    Alert,
 }
 
 impl Actor {
@@ -32,18 +91,180 @@ impl Actor {
     }
 
     pub fn new(kind_id: String, character_stats: Option<CharacterStats>, position: Position) -> Self {
-        let kind = ASSETS.actor_kinds.iter().find(|k| k.id == kind_id).unwrap();
-
-        Self {
+        let mut actor = Self {
             kind_id,
             character_stats,
 
             position,
-            health: kind.health,
+            health: 0,
             ai_state: ActorAiState::Idle,
+            status_effects: Vec::new(),
+            guard_post: None,
+            awareness: Awareness::Unaware,
+        };
+        if actor.character_stats.is_some() {
+            // characters are never caught off guard
+            actor.awareness = Awareness::Alert;
+        } else {
+            match actor.kind().behaviour.idle {
+                IdleBehaviour::Sleep => actor.ai_state = ActorAiState::Sleeping,
+                IdleBehaviour::Guard => {
+                    actor.ai_state = ActorAiState::Guarding(position);
+                    actor.guard_post = Some(position);
+                }
+                IdleBehaviour::Stand | IdleBehaviour::Wander => {}
+            }
+        }
+        actor.health = actor.max_health();
+        let max_mana = actor.max_mana();
+        actor.restore_mana(max_mana);
+        actor
+    }
+
+    /// The character name for player characters, otherwise the name of the actor kind.
+    pub fn name(&self) -> String {
+        match self.character_stats.as_ref() {
+            Some(stats) => stats.name.clone(),
+            None => ASSETS.actor_kinds.iter().find(|k| k.id == self.kind_id).unwrap().name.clone(),
+        }
+    }
+
+    /// Race and class for characters, such as "Dwarf Warrior", otherwise the name of the actor kind.
+    pub fn title(&self) -> String {
+        let kind = ASSETS.actor_kinds.iter().find(|k| k.id == self.kind_id).unwrap();
+        match self.character_class() {
+            Some(class) => format!("{} {}", kind.name, class.name),
+            None => kind.name.clone(),
+        }
+    }
+
+    /// The name as it reads mid-sentence, "a Goblin" for monsters but just the name for characters.
+    pub fn indefinite_name(&self) -> String {
+        match self.character_stats.as_ref() {
+            Some(stats) => stats.name.clone(),
+            None => format!("a {}", self.name()),
         }
     }
 
+    /// The name as it reads when the actor is already known, "the Goblin" for monsters.
+    pub fn definite_name(&self) -> String {
+        match self.character_stats.as_ref() {
+            Some(stats) => stats.name.clone(),
+            None => format!("the {}", self.name()),
+        }
+    }
+
+    fn kind(&self) -> &'static ActorKind {
+        ASSETS.actor_kinds.iter().find(|k| k.id == self.kind_id).unwrap()
+    }
+
+    pub fn ai_state(&self) -> &ActorAiState {
+        &self.ai_state
+    }
+
+    /// What the player can tell the actor is up to when looking at it, if anything out of the ordinary.
+    pub fn ai_state_description(&self) -> Option<&'static str> {
+        match (&self.ai_state, self.awareness) {
+            (ActorAiState::Sleeping, _) => Some("asleep"),
+            (ActorAiState::Fleeing(_), _) => Some("fleeing"),
+            (_, Awareness::Unaware) => Some("unaware"),
+            (_, Awareness::Suspicious) => Some("suspicious"),
+            (_, Awareness::Alert) => None,
+        }
+    }
+
+    pub fn awareness(&self) -> Awareness {
+        self.awareness
+    }
+
+    /// Reacts to a noise heard at some volume, going to look unless busy fighting or fleeing.
+    pub fn hear_noise(&mut self, position: Position, volume: i32) {
+        if matches!(self.ai_state, ActorAiState::Sleeping) && volume < WAKE_VOLUME {
+            return;
+        }
+        self.investigate(position);
+    }
+
+    /// Grows suspicious and goes to look at a position, unless busy fighting or fleeing.
+    fn investigate(&mut self, position: Position) {
+        match self.ai_state {
+            ActorAiState::TargetingActor(_) | ActorAiState::Fleeing(_) => {}
+            ActorAiState::Idle | ActorAiState::InvestigatingPosition(_) | ActorAiState::Wandering(_) | ActorAiState::Sleeping | ActorAiState::Guarding(_) => {
+                if self.awareness == Awareness::Unaware {
+                    self.awareness = Awareness::Suspicious;
+                }
+                self.ai_state = ActorAiState::InvestigatingPosition(position);
+            }
+        }
+    }
+
+    /// Rolls to spot a visible hostile, returning where one was seen. The roll gets harder with distance, darkness
+    /// and the hostile's stealth; alert monsters keep track of whatever they can see without rolling.
+    pub fn spot_hostile(&self, actor_id: usize, actors: &ActorManager, map: &MapManager, factions: &FactionRelations, rng: &mut ChaCha12Rng) -> Option<Position> {
+        let visible_tiles = map.shadowcast(self.position, self.sight_radius());
+        let sleeping = matches!(self.ai_state, ActorAiState::Sleeping);
+        let bonus = if self.awareness == Awareness::Suspicious { SUSPICIOUS_BONUS } else { 0 };
+        for other_actor_id in self.visible_hostiles(actor_id, actors, map, factions, &visible_tiles) {
+            let other_position = actors.get_actor(other_actor_id).unwrap().position();
+            let distance = self.position.chebyshev_distance(other_position);
+            if sleeping && distance > SLEEP_WAKE_DISTANCE {
+                continue;
+            }
+            if self.awareness == Awareness::Alert {
+                return Some(other_position);
+            }
+
+            let mut difficulty = SPOT_DIFFICULTY + distance as i32 + actors.get_actor(other_actor_id).unwrap().stealth();
+            if !map.is_lit(other_position) {
+                difficulty += DARKNESS_PENALTY;
+            }
+            if sleeping {
+                difficulty += SLEEPING_PENALTY;
+            }
+            if rng.random_range(1..=20) + bonus >= difficulty {
+                return Some(other_position);
+            }
+        }
+        None
+    }
+
+    /// Raises awareness after spotting a hostile, suspicious monsters going to look and alert ones giving chase,
+    /// or lets it slowly settle again once the monster has nothing to do.
+    pub fn update_awareness(&mut self, spotted: Option<Position>, rng: &mut ChaCha12Rng) {
+        match spotted {
+            Some(position) if self.awareness == Awareness::Unaware => self.investigate(position),
+            Some(_) => self.awareness = Awareness::Alert,
+            None => {
+                let settled = matches!(self.ai_state, ActorAiState::Idle | ActorAiState::Wandering(_) | ActorAiState::Sleeping | ActorAiState::Guarding(_));
+                if settled && rng.random_bool(CALM_DOWN_CHANCE) {
+                    self.awareness = match self.awareness {
+                        Awareness::Alert => Awareness::Suspicious,
+                        Awareness::Suspicious | Awareness::Unaware => Awareness::Unaware,
+                    };
+                }
+            }
+        }
+    }
+
+    /// How hard the actor is to spot, from dexterity less the penalties of any heavy armor worn.
+    pub fn stealth(&self) -> i32 {
+        let armor_penalty: i32 = self
+            .equipped_armor()
+            .iter()
+            .map(|item_kind| match &item_kind.item_type {
+                ItemType::Armor { stealth_penalty, .. } => *stealth_penalty,
+                _ => 0,
+            })
+            .sum();
+        self.attributes().stealth_bonus() - armor_penalty
+    }
+
+    /// Whether the actor is hurt badly enough that its behaviour has it run away.
+    fn should_flee(&self) -> bool {
+        let flee_below = self.kind().behaviour.flee_below;
+        flee_below > 0.0 && (self.health as f64) < self.max_health() as f64 * flee_below
+    }
+
     pub fn glyph(&self) -> (char, Color) {
         let kind = ASSETS.actor_kinds.iter().find(|k| k.id == self.kind_id).unwrap();
         (kind.glyph, kind.color)
@@ -57,14 +278,290 @@ impl Actor {
         self.health
     }
 
+    /// A rough description of how hurt the actor is, for looking at them.
+    pub fn health_description(&self) -> &'static str {
+        match self.health * 100 / self.max_health().max(1) {
+            100.. => "unhurt",
+            75..=99 => "lightly wounded",
+            50..=74 => "wounded",
+            25..=49 => "badly wounded",
+            _ => "almost dead",
+        }
+    }
+
     pub fn max_health(&self) -> i32 {
         let kind = ASSETS.actor_kinds.iter().find(|k| k.id == self.kind_id).unwrap();
-        kind.health
+        let class_bonus = self.character_class().map_or(0, |class| class.health_bonus);
+        let level_bonus = (self.level() as i32 - 1) * HEALTH_PER_LEVEL;
+        kind.health + class_bonus + level_bonus + self.attributes().health_bonus()
+    }
+
+    /// Mana for characters whose class has a mana pool, monsters cast without it.
+    pub fn mana(&self) -> i32 {
+        self.character_stats.as_ref().map_or(0, |stats| stats.mana)
+    }
+
+    pub fn max_mana(&self) -> i32 {
+        match self.character_class() {
+            Some(class) if class.mana > 0 => (class.mana + self.attributes().mana_bonus()).max(0),
+            _ => 0,
+        }
+    }
+
+    /// Pays for a spell out of the mana pool. Monsters have no pool and always succeed.
+    pub fn spend_mana(&mut self, amount: i32) -> Result<(), String> {
+        let Some(stats) = self.character_stats.as_mut() else {
+            return Ok(());
+        };
+        if stats.mana < amount {
+            return Err("Not enough mana".to_string());
+        }
+        stats.mana -= amount;
+        Ok(())
+    }
+
+    /// Restores mana up to the maximum and returns how much was actually restored.
+    pub fn restore_mana(&mut self, amount: i32) -> i32 {
+        let max_mana = self.max_mana();
+        let Some(stats) = self.character_stats.as_mut() else {
+            return 0;
+        };
+        let old_mana = stats.mana;
+        stats.mana = (stats.mana + amount).min(max_mana.max(old_mana));
+        stats.mana - old_mana
+    }
+
+    /// Restores health up to the maximum and returns how much was actually healed.
+    pub fn heal(&mut self, amount: i32) -> i32 {
+        let old_health = self.health;
+        self.health = (self.health + amount).min(self.max_health().max(old_health));
+        self.health - old_health
+    }
+
+    /// Spells the actor can cast: from its class and a wielded staff for characters, from its kind for monsters.
+    pub fn known_spells(&self) -> Vec<&'static SpellKind> {
+        let mut spell_ids: Vec<&String> = match self.character_class() {
+            Some(class) => class.spells.iter().collect(),
+            None => ASSETS.actor_kinds.iter().find(|k| k.id == self.kind_id).unwrap().spells.iter().collect(),
+        };
+        if let Some(ItemType::Weapon { spells, .. }) = self.wielded_staff().map(|staff| &staff.item_type) {
+            spell_ids.extend(spells.iter().filter(|spell_id| !spell_ids.contains(spell_id)).collect::<Vec<_>>());
+        }
+        spell_ids.into_iter().filter_map(|spell_id| ASSETS.spell_kinds.iter().find(|spell| &spell.id == spell_id)).collect()
+    }
+
+    /// Damage a spell does when this actor casts it, boosted by intelligence and any wielded staff.
+    pub fn spell_damage(&self, spell: &SpellKind) -> Dice {
+        if spell.damage.max() <= 0 {
+            return Dice::default();
+        }
+        let staff_power = match self.wielded_staff().map(|staff| &staff.item_type) {
+            Some(ItemType::Weapon { spell_power, .. }) => *spell_power,
+            _ => 0,
+        };
+        spell.damage.plus(staff_power + self.attributes().spell_damage_bonus())
+    }
+
+    fn wielded_staff(&self) -> Option<&'static ItemKind> {
+        let weapon_id = self.character_stats.as_ref()?.equipment.weapon.as_ref()?;
+        ASSETS.item_kinds.iter().find(|kind| &kind.id == weapon_id && matches!(&kind.item_type, ItemType::Weapon { weapon_type: WeaponType::Staff, .. }))
+    }
+
+    /// The shot the actor can make: a wielded ranged weapon and matching ammunition for characters, the kind's ranged attack for monsters.
+    pub fn ranged_attack(&self) -> Result<RangedAttack, String> {
+        let Some(stats) = self.character_stats.as_ref() else {
+            let kind = ASSETS.actor_kinds.iter().find(|k| k.id == self.kind_id).unwrap();
+            return match &kind.ranged {
+                Some(ranged) => Ok(RangedAttack {
+                    damage: ranged.damage,
+                    damage_type: ranged.damage_type,
+                    range: ranged.range,
+                    ammo_id: None,
+                }),
+                None => Err("Cannot attack from range".to_string()),
+            };
+        };
+
+        let weapon = stats.equipment.weapon.as_ref().and_then(|weapon_id| ASSETS.item_kinds.iter().find(|kind| &kind.id == weapon_id));
+        let Some(ItemType::Weapon { damage, damage_type, weapon_type, range, .. }) = weapon.map(|weapon| &weapon.item_type) else {
+            return Err("You are not wielding a ranged weapon".to_string());
+        };
+        if *range <= 0 {
+            return Err("You are not wielding a ranged weapon".to_string());
+        }
+
+        // the first matching ammunition in the pack is fired
+        for item in stats.inventory.items() {
+            if let Some(ItemKind {
+                item_type: ItemType::Ammo { damage: ammo_damage, weapon_type: ammo_type },
+                ..
+            }) = ASSETS.item_kinds.iter().find(|kind| kind.id == item.item_id)
+                && ammo_type == weapon_type
+            {
+                return Ok(RangedAttack {
+                    damage: damage.plus(*ammo_damage),
+                    damage_type: *damage_type,
+                    range: *range,
+                    ammo_id: Some(item.item_id.clone()),
+                });
+            }
+        }
+        Err("You have no ammunition".to_string())
+    }
+
+    /// Chance each turn that a monster casts one of its spells.
+    pub fn spell_chance(&self) -> f64 {
+        ASSETS.actor_kinds.iter().find(|k| k.id == self.kind_id).unwrap().spell_chance
+    }
+
+    /// Experience level for characters, monsters are always level 1.
+    pub fn level(&self) -> u32 {
+        self.character_stats.as_ref().map_or(1, |stats| stats.level)
+    }
+
+    pub fn experience(&self) -> u32 {
+        self.character_stats.as_ref().map_or(0, |stats| stats.experience)
+    }
+
+    /// Experience awarded to whoever kills this actor.
+    pub fn experience_value(&self) -> u32 {
+        ASSETS.actor_kinds.iter().find(|k| k.id == self.kind_id).unwrap().experience
+    }
+
+    /// Status effects the actor's melee hits may inflict.
+    pub fn attack_effects(&self) -> &'static [AttackEffect] {
+        &ASSETS.actor_kinds.iter().find(|k| k.id == self.kind_id).unwrap().attack_effects
+    }
+
+    /// Attribute points gained from levelling that have not been spent yet.
+    pub fn unspent_points(&self) -> i32 {
+        self.character_stats.as_ref().map_or(0, |stats| stats.unspent_points)
+    }
+
+    /// Adds experience for characters and returns how many levels were gained.
+    /// Each level raises max health and current health by the same amount and grants attribute points.
+    pub fn gain_experience(&mut self, amount: u32) -> u32 {
+        let old_max_health = self.max_health();
+        let Ok(stats) = self.character_stats_mut() else {
+            return 0;
+        };
+
+        stats.experience += amount;
+        let mut levels_gained = 0;
+        while stats.experience >= experience_for_level(stats.level + 1) {
+            stats.level += 1;
+            stats.unspent_points += ATTRIBUTE_POINTS_PER_LEVEL;
+            levels_gained += 1;
+        }
+
+        self.health += self.max_health() - old_max_health;
+        levels_gained
+    }
+
+    /// Spends unspent attribute points, keeping current health in step with any change to max health.
+    pub fn spend_attribute_points(&mut self, points: Attributes) -> Result<(), String> {
+        let old_max_health = self.max_health();
+        let stats = self.character_stats_mut()?;
+
+        if points.total() > stats.unspent_points {
+            return Err("Not enough attribute points".to_string());
+        }
+        stats.unspent_points -= points.total();
+        stats.attributes = stats.attributes + points;
+
+        self.health = (self.health + self.max_health() - old_max_health).min(self.max_health());
+        Ok(())
+    }
+
+    /// The actor's own attributes for characters, otherwise the base attributes of its kind, plus any status effects.
+    pub fn attributes(&self) -> Attributes {
+        let base = match self.character_stats.as_ref() {
+            Some(stats) => stats.attributes,
+            None => ASSETS.actor_kinds.iter().find(|k| k.id == self.kind_id).unwrap().attributes,
+        };
+        base + self.stat_modifiers().attributes
+    }
+
+    /// Everything active status effects change about the actor, which every stat calculation goes through.
+    pub fn stat_modifiers(&self) -> StatModifiers {
+        StatModifiers::from_effects(&self.status_effects)
+    }
+
+    pub fn status_effects(&self) -> &[StatusEffect] {
+        &self.status_effects
+    }
+
+    pub fn is_confused(&self) -> bool {
+        self.stat_modifiers().confused
+    }
+
+    /// How many tiles away the actor can see.
+    pub fn sight_radius(&self) -> i64 {
+        if self.stat_modifiers().blind { 1 } else { VISION_RADIUS }
+    }
+
+    /// Applies a status effect for a number of turns, stacking it with an existing one of the same kind.
+    pub fn add_status_effect(&mut self, kind_id: &str, magnitude: i32, duration: i32) -> Result<&'static StatusEffectKind, String> {
+        let kind = ASSETS.status_effect_kinds.iter().find(|kind| kind.id == kind_id).ok_or_else(|| format!("Unknown status effect {}", kind_id))?;
+        let effect = StatusEffect::new(kind_id.to_string(), magnitude, duration);
+        match self.status_effects.iter_mut().find(|existing| existing.kind_id() == kind_id) {
+            Some(existing) => existing.stack(effect),
+            None => self.status_effects.push(effect),
+        }
+        Ok(kind)
+    }
+
+    /// Lets game time pass for the actor, regenerating mana and ticking status effects.
+    pub fn pass_time(&mut self, action_points: u32) -> StatusTick {
+        if let Some(stats) = self.character_stats.as_mut() {
+            stats.mana_elapsed += action_points;
+            let regenerated = (stats.mana_elapsed / MANA_REGEN_INTERVAL) as i32;
+            stats.mana_elapsed %= MANA_REGEN_INTERVAL;
+            self.restore_mana(regenerated);
+        }
+        self.tick_status_effects(action_points)
+    }
+
+    /// Lets game time pass for the actor's status effects, applying per turn health changes and removing expired effects.
+    pub fn tick_status_effects(&mut self, action_points: u32) -> StatusTick {
+        let mut tick = StatusTick::default();
+        let max_health = self.max_health();
+        for effect in &mut self.status_effects {
+            let change = effect.advance(action_points);
+            if self.health <= 0 {
+                continue;
+            }
+
+            let old_health = self.health;
+            self.health = (self.health + change).min(max_health.max(old_health));
+            if self.health != old_health {
+                tick.health_changes.push((effect.kind(), self.health - old_health));
+            }
+            if self.health <= 0 {
+                tick.killed_by = Some(effect.kind());
+            }
+        }
+
+        tick.expired = self.status_effects.iter().filter(|effect| effect.is_expired()).map(|effect| effect.kind()).collect();
+        self.status_effects.retain(|effect| !effect.is_expired());
+        self.health = self.health.min(self.max_health());
+        tick
+    }
+
+    pub fn accuracy_bonus(&self) -> i32 {
+        self.attributes().accuracy_bonus()
+    }
+
+    /// The class chosen at character creation, if this actor has one.
+    pub fn character_class(&self) -> Option<&'static CharacterClass> {
+        let stats = self.character_stats.as_ref()?;
+        ASSETS.character_classes.iter().find(|c| c.id == stats.class_id)
     }
 
     pub fn speed(&self) -> u32 {
         let kind = ASSETS.actor_kinds.iter().find(|k| k.id == self.kind_id).unwrap();
-        kind.speed
+        let speed = kind.speed as i32 + self.attributes().speed_bonus();
+        (speed * (100 + self.stat_modifiers().speed_percent) / 100).max(MIN_SPEED) as u32
     }
 
     pub fn set_position(&mut self, position: Position) {
@@ -75,14 +572,57 @@ impl Actor {
         self.ai_state = state;
     }
 
-    pub fn melee_attack_roll(&self) -> i32 {
-        self.weapon_damage()
+    pub fn melee_damage(&self) -> (Dice, DamageType) {
+        let (damage, damage_type) = self.weapon_damage();
+        (damage.plus(self.attributes().damage_bonus()), damage_type)
     }
 
-    pub fn apply_damage(&mut self, damage: i32) -> ApplyDamageResult {
-        let actual_damage = (damage - self.total_defense()).max(1); // Minimum 1 damage
-        self.health -= actual_damage;
-        if self.health <= 0 { ApplyDamageResult::ActorDied } else { ApplyDamageResult::None }
+    /// Rolls to hit the target with an attack of the given damage, rolling the damage as well if it lands.
+    pub fn attack_roll(&self, target: &Actor, damage: Dice, rng: &mut ChaCha12Rng) -> AttackOutcome {
+        let roll = rng.random_range(1..=20);
+        if roll >= CRITICAL_ROLL {
+            // critical hits always do at least the most the dice can roll
+            return AttackOutcome::Crit(damage.max() + damage.roll(rng));
+        }
+        if roll == 1 || roll + self.accuracy_bonus() < HIT_DIFFICULTY + target.evasion() {
+            return AttackOutcome::Miss;
+        }
+        AttackOutcome::Hit(damage.roll(rng))
+    }
+
+    /// Takes a hit after resistances, vulnerabilities and armor have had their say, returning how the damage worked out.
+    pub fn apply_damage(&mut self, damage: i32, damage_type: DamageType, rng: &mut ChaCha12Rng) -> DamageBreakdown {
+        let modifier_percent = self.damage_modifier_percent(damage_type);
+        let modified = damage * (100 + modifier_percent).max(0) / 100;
+        let soaked = if damage_type.is_physical() { self.armor_soak().iter().map(|soak| soak.roll(rng)).sum() } else { 0 };
+        // Minimum 1 damage, unless the actor is immune
+        let taken = if modifier_percent <= -100 { 0 } else { (modified - soaked).max(1) };
+
+        self.health -= taken;
+        if let ActorAiState::Sleeping = self.ai_state {
+            self.ai_state = ActorAiState::Idle;
+        }
+        self.awareness = Awareness::Alert;
+        let result = if self.health <= 0 { ApplyDamageResult::ActorDied } else { ApplyDamageResult::None };
+        DamageBreakdown {
+            damage_type,
+            modifier_percent,
+            soaked: soaked.min(modified),
+            taken,
+            result,
+        }
+    }
+
+    /// Percentage change to damage of a type, negative when resisted and positive when vulnerable.
+    pub fn damage_modifier_percent(&self, damage_type: DamageType) -> i32 {
+        let kind = ASSETS.actor_kinds.iter().find(|k| k.id == self.kind_id).unwrap();
+        let mut percent = kind.vulnerabilities.get(&damage_type).copied().unwrap_or(0) - kind.resistances.get(&damage_type).copied().unwrap_or(0);
+        for item_kind in self.equipped_armor() {
+            if let ItemType::Armor { resistances, vulnerabilities, .. } = &item_kind.item_type {
+                percent += vulnerabilities.get(&damage_type).copied().unwrap_or(0) - resistances.get(&damage_type).copied().unwrap_or(0);
+            }
+        }
+        percent.max(-100)
     }
 
     // Inventory methods
@@ -111,6 +651,19 @@ impl Actor {
         Ok(inventory.remove_item(item_id, quantity))
     }
 
+    /// Everything the actor leaves behind when it dies, its rolled loot plus anything it was carrying.
+    pub fn take_dropped_items(&mut self, rng: &mut ChaCha12Rng) -> Vec<InventoryItem> {
+        let kind = ASSETS.actor_kinds.iter().find(|k| k.id == self.kind_id).unwrap();
+        let mut items: Vec<InventoryItem> = kind.loot.iter().filter(|entry| rng.random_bool(entry.chance.clamp(0.0, 1.0))).map(|entry| InventoryItem { item_id: entry.item.clone(), quantity: entry.quantity }).collect();
+
+        if let Some(stats) = self.character_stats.as_mut() {
+            items.extend(stats.inventory.take_all());
+            items.extend(stats.equipment.take_all().into_iter().map(|item_id| InventoryItem { item_id, quantity: 1 }));
+        }
+
+        items
+    }
+
     // Equipment methods
     pub fn equipment(&self) -> Option<&Equipment> {
         match self.character_stats.as_ref() {
@@ -154,6 +707,21 @@ impl Actor {
         }
     }
 
+    /// Hands out the starting kit of the actor's class, equipping the items marked for it.
+    pub fn add_starting_items(&mut self) -> Result<(), String> {
+        let Some(class) = self.character_class() else {
+            return Ok(());
+        };
+
+        for starting_item in &class.starting_items {
+            self.add_item(starting_item.item.clone(), starting_item.quantity)?;
+            if starting_item.equipped {
+                self.equip_item_from_inventory(&starting_item.item)?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn equip_item(&mut self, item_kind: &ItemKind) -> Result<Option<String>, String> {
         let stats = self.character_stats_mut()?;
         stats.equipment.equip_item(item_kind)
@@ -177,8 +745,9 @@ impl Actor {
             _ => return Err("Item is not consumable".to_string()),
         };
 
-        // Apply the effect
+        // Apply the effect and use up the item
         let result_message = self.apply_consumable_effect(effect)?;
+        self.remove_item(item_id, 1)?;
 
         Ok(result_message)
     }
@@ -186,19 +755,22 @@ impl Actor {
     fn apply_consumable_effect(&mut self, effect: &ConsumableEffect) -> Result<String, String> {
         match effect {
             ConsumableEffect::Heal { amount } => {
-                let old_health = self.health;
-                self.health = (self.health + amount).min(self.max_health());
-                let actual_healing = self.health - old_health;
+                let actual_healing = self.heal(*amount);
                 Ok(format!("Restored {} health", actual_healing))
             }
             ConsumableEffect::RestoreMana { amount } => {
-                // TODO: Implement mana system
-                Ok(format!("Would restore {} mana (not implemented)", amount))
+                if self.max_mana() == 0 {
+                    return Err("You have no mana to restore".to_string());
+                }
+                let actual_mana = self.restore_mana(*amount);
+                Ok(format!("Restored {} mana", actual_mana))
             }
             ConsumableEffect::Buff { stat, amount, duration } => {
-                // TODO: Implement buff system
-                Ok(format!("Would apply +{} {} for {} turns (not implemented)", amount, stat, duration))
+                let kind = self.add_status_effect(stat, *amount, *duration)?;
+                Ok(format!("You are {}", kind.adjective))
             }
+            // the blast itself is dealt out by the caller, who can reach the other actors
+            ConsumableEffect::Burst { damage_type, .. } => Ok(format!("It bursts in a blast of {}", damage_type.name())),
         }
     }
 
@@ -220,68 +792,111 @@ impl Actor {
         None
     }
 
-    // Calculate total defense from equipped armor
-    pub fn total_defense(&self) -> i32 {
-        let mut defense = 0;
+    /// How hard the actor is to hit, from dexterity and status effects.
+    pub fn evasion(&self) -> i32 {
+        self.attributes().defense_bonus() + self.stat_modifiers().defense
+    }
 
-        if let Some(stats) = self.character_stats.as_ref() {
-            for (_, equipped_item) in stats.equipment.iter_slots() {
-                if let Some(item_id) = equipped_item {
-                    if let Some(item_kind) = ASSETS.item_kinds.iter().find(|k| &k.id == item_id) {
-                        if let ItemType::Armor { defense: item_defense, .. } = &item_kind.item_type {
-                            defense += item_defense;
-                        }
-                    }
-                }
+    /// The soak of the actor's natural armor and each piece of equipped armor, each rolled separately against a hit.
+    pub fn armor_soak(&self) -> Vec<Dice> {
+        let kind = ASSETS.actor_kinds.iter().find(|k| k.id == self.kind_id).unwrap();
+        let mut soak = vec![kind.soak];
+        for item_kind in self.equipped_armor() {
+            if let ItemType::Armor { soak: item_soak, .. } = &item_kind.item_type {
+                soak.push(*item_soak);
             }
         }
+        soak
+    }
 
-        defense
+    fn equipped_armor(&self) -> Vec<&'static ItemKind> {
+        let Some(stats) = self.character_stats.as_ref() else {
+            return Vec::new();
+        };
+        stats
+            .equipment
+            .iter_slots()
+            .into_iter()
+            .filter_map(|(_, equipped_item)| equipped_item.as_ref())
+            .filter_map(|item_id| ASSETS.item_kinds.iter().find(|k| &k.id == item_id))
+            .filter(|item_kind| matches!(item_kind.item_type, ItemType::Armor { .. }))
+            .collect()
     }
 
-    pub fn weapon_damage(&self) -> i32 {
-        if let Some(stats) = self.character_stats.as_ref() {
-            if let Some(weapon_id) = &stats.equipment.weapon {
-                if let Some(weapon_kind) = ASSETS.item_kinds.iter().find(|k| &k.id == weapon_id) {
-                    if let ItemType::Weapon { damage, .. } = &weapon_kind.item_type {
-                        return *damage;
-                    }
-                }
-            }
+    /// The least and most the actor's armor can soak from one hit.
+    pub fn soak_range(&self) -> (i32, i32) {
+        let soak = self.armor_soak();
+        (soak.iter().map(|soak| soak.min()).sum(), soak.iter().map(|soak| soak.max()).sum())
+    }
+
+    /// The damage of the wielded weapon, or of the actor's own attack when unarmed.
+    pub fn weapon_damage(&self) -> (Dice, DamageType) {
+        if let Some(stats) = self.character_stats.as_ref()
+            && let Some(weapon_id) = &stats.equipment.weapon
+            && let Some(weapon_kind) = ASSETS.item_kinds.iter().find(|k| &k.id == weapon_id)
+            && let ItemType::Weapon { damage, damage_type, .. } = &weapon_kind.item_type
+        {
+            return (*damage, *damage_type);
         }
-        // Base unarmed damage
-        1
+        let kind = ASSETS.actor_kinds.iter().find(|k| k.id == self.kind_id).unwrap();
+        (kind.damage, kind.damage_type)
     }
 
-    pub fn ai_turn(&self, actor_id: usize, actors: &ActorManager, map: &MapManager) -> (ActorAiState, Action) {
+    /// Sometimes picks a spell to cast at a visible target, healing spells only once badly hurt.
+    fn choose_spell(&self, target_position: Position, rng: &mut ChaCha12Rng) -> Option<Action> {
+        let spell_chance = self.spell_chance();
+        if spell_chance <= 0.0 || !rng.random_bool(spell_chance) {
+            return None;
+        }
+
+        let distance = self.position.chebyshev_distance(target_position);
+        let usable_spells: Vec<&SpellKind> = self
+            .known_spells()
+            .into_iter()
+            .filter(|spell| match spell.target {
+                SpellTarget::Caster => spell.heal == 0 || self.health * 2 < self.max_health(),
+                SpellTarget::Other => distance <= spell.range,
+            })
+            .collect();
+
+        let spell = usable_spells.choose(rng)?;
+        let position = if spell.target == SpellTarget::Caster { self.position } else { target_position };
+        Some(Action::CastSpell(spell.id.clone(), position))
+    }
+
+    pub fn ai_turn(&self, actor_id: usize, actors: &ActorManager, map: &MapManager, factions: &FactionRelations, rng: &mut ChaCha12Rng) -> (ActorAiState, Action) {
         let mut current_state = self.ai_state.clone();
-        let visible_tiles = map.shadowcast(self.position);
+        let visible_tiles = map.shadowcast(self.position, self.sight_radius());
 
         loop {
             match &current_state {
                 ActorAiState::Idle => {
-                    // try to find a target
-                    let mut possible_targets = Vec::new();
-                    for visible_position in &visible_tiles {
-                        if let Some(tile) = map.get_tile(*visible_position) {
-                            if let Some(other_actor_id) = tile.actor_id() {
-                                if other_actor_id != actor_id {
-                                    let target_actor = actors.get_actor(other_actor_id).unwrap();
-                                    if !self.is_friendly_towards(target_actor) {
-                                        possible_targets.push(other_actor_id);
-                                    }
-                                }
-                            }
-                        }
-                    }
-
-                    if !possible_targets.is_empty() {
-                        possible_targets.shuffle(&mut rand::rng());
-                        let target_actor_id = possible_targets.pop().unwrap();
+                    if let Some(target_actor_id) = self.find_target(actor_id, actors, map, factions, &visible_tiles, rng) {
                         current_state = ActorAiState::TargetingActor(target_actor_id);
                         continue;
                     }
 
+                    // nothing to fight, fall back on the kind's behaviour
+                    match self.kind().behaviour.idle {
+                        IdleBehaviour::Wander => {
+                            let destinations: Vec<&Position> = visible_tiles
+                                .iter()
+                                .filter(|position| **position != self.position && position.chebyshev_distance(self.position) <= WANDER_DISTANCE)
+                                .filter(|position| map.get_tile(**position).is_some_and(|tile| tile.is_walkable() && tile.actor_id().is_none()))
+                                .collect();
+                            if let Some(destination) = destinations.choose(rng) {
+                                return (ActorAiState::Wandering(**destination), Action::Wait);
+                            }
+                        }
+                        IdleBehaviour::Guard => {
+                            if let Some(guard_post) = self.guard_post {
+                                current_state = ActorAiState::Guarding(guard_post);
+                                continue;
+                            }
+                        }
+                        IdleBehaviour::Stand | IdleBehaviour::Sleep => {}
+                    }
+
                     // no target found, remain idle
                     return (ActorAiState::Idle, Action::Wait);
                 }
@@ -299,22 +914,53 @@ impl Actor {
                     // make sure target is still visible
                     if !visible_tiles.contains(&target_actor.position()) {
                         // lost sight of target, investigate last known position
-                        let target_position = target_actor.position().clone();
+                        let target_position = target_actor.position();
                         current_state = ActorAiState::InvestigatingPosition(target_position);
                         continue;
                     }
 
+                    if self.should_flee() {
+                        current_state = ActorAiState::Fleeing(*other_actor_id);
+                        continue;
+                    }
+
+                    // guards let go of anything that leads them too far from their post
+                    if let Some(guard_post) = self.guard_post
+                        && target_actor.position().chebyshev_distance(guard_post) > GUARD_RADIUS
+                    {
+                        current_state = ActorAiState::Guarding(guard_post);
+                        continue;
+                    }
+
+                    if let Some(action) = self.choose_spell(target_actor.position(), rng) {
+                        return (ActorAiState::TargetingActor(*other_actor_id), action);
+                    }
+
+                    // ranged attackers back off when the target gets close and shoot when they have a clear line
+                    if let Ok(ranged_attack) = self.ranged_attack() {
+                        let target_position = target_actor.position();
+                        let distance = self.position.chebyshev_distance(target_position);
+                        if distance < RANGED_KEEP_DISTANCE
+                            && let Some(retreat_position) = Self::step_away(map, self.position, target_position)
+                        {
+                            return (ActorAiState::TargetingActor(*other_actor_id), Action::MoveTo(retreat_position));
+                        }
+                        if distance <= ranged_attack.range && map.trace_projectile(self.position, target_position).last() == Some(&target_position) {
+                            return (ActorAiState::TargetingActor(*other_actor_id), Action::RangedAttack(target_position));
+                        }
+                    }
+
                     if self.position.is_adjacent(target_actor.position) {
                         // attack target
                         return (ActorAiState::TargetingActor(*other_actor_id), Action::MeleeAttack(*other_actor_id));
                     } else {
                         // move towards target
-                        let path = a_star(actors, map, self.position, target_actor.position(), actor_id);
-                        if let Some(path) = path {
-                            if !path.is_empty() {
-                                let next_position = path[0].clone();
-                                return (ActorAiState::TargetingActor(*other_actor_id), Action::MoveTo(next_position));
-                            }
+                        let path = a_star(actors, map, factions, self.position, target_actor.position(), actor_id);
+                        if let Some(path) = path
+                            && !path.is_empty()
+                        {
+                            let next_position = path[0];
+                            return (ActorAiState::TargetingActor(*other_actor_id), Self::step_towards(map, next_position));
                         }
 
                         // cannot path to target, go idle
@@ -324,22 +970,7 @@ impl Actor {
 
                 ActorAiState::InvestigatingPosition(target_position) => {
                     // check if any targets can be seen from this position
-                    let mut possible_targets = Vec::new();
-                    for visible_position in &visible_tiles {
-                        if let Some(tile) = map.get_tile(*visible_position) {
-                            if let Some(other_actor_id) = tile.actor_id() {
-                                if other_actor_id != actor_id {
-                                    let target_actor = actors.get_actor(other_actor_id).unwrap();
-                                    if !self.is_friendly_towards(target_actor) {
-                                        possible_targets.push(other_actor_id);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    if !possible_targets.is_empty() {
-                        possible_targets.shuffle(&mut rand::rng());
-                        let target_actor_id = possible_targets.pop().unwrap();
+                    if let Some(target_actor_id) = self.find_target(actor_id, actors, map, factions, &visible_tiles, rng) {
                         current_state = ActorAiState::TargetingActor(target_actor_id);
                         continue;
                     }
@@ -350,54 +981,184 @@ impl Actor {
                     }
 
                     // move towards investigation position
-                    let path = a_star(actors, map, self.position, *target_position, actor_id);
-                    if let Some(path) = path {
-                        if !path.is_empty() {
-                            let next_position = path[0].clone();
-                            return (ActorAiState::InvestigatingPosition(target_position.clone()), Action::MoveTo(next_position));
-                        }
+                    let path = a_star(actors, map, factions, self.position, *target_position, actor_id);
+                    if let Some(path) = path
+                        && !path.is_empty()
+                    {
+                        let next_position = path[0];
+                        return (ActorAiState::InvestigatingPosition(*target_position), Self::step_towards(map, next_position));
                     }
 
                     // cannot reach position, go idle
                     return (ActorAiState::Idle, Action::Wait);
                 }
+
+                ActorAiState::Fleeing(threat_actor_id) => {
+                    let Some(threat_actor) = actors.get_actor(*threat_actor_id) else {
+                        current_state = ActorAiState::Idle;
+                        continue;
+                    };
+
+                    // out of sight is safe enough, and a monster that has recovered turns to fight again
+                    if !visible_tiles.contains(&threat_actor.position()) {
+                        return (ActorAiState::Idle, Action::Wait);
+                    }
+                    if !self.should_flee() {
+                        current_state = ActorAiState::TargetingActor(*threat_actor_id);
+                        continue;
+                    }
+
+                    if let Some(escape_position) = Self::step_away(map, self.position, threat_actor.position()) {
+                        return (ActorAiState::Fleeing(*threat_actor_id), Self::step_towards(map, escape_position));
+                    }
+                    // cornered, so fight back
+                    if self.position.is_adjacent(threat_actor.position()) {
+                        return (ActorAiState::Fleeing(*threat_actor_id), Action::MeleeAttack(*threat_actor_id));
+                    }
+                    return (ActorAiState::Fleeing(*threat_actor_id), Action::Wait);
+                }
+
+                ActorAiState::Wandering(destination) => {
+                    if let Some(target_actor_id) = self.find_target(actor_id, actors, map, factions, &visible_tiles, rng) {
+                        current_state = ActorAiState::TargetingActor(target_actor_id);
+                        continue;
+                    }
+
+                    if &self.position == destination {
+                        return (ActorAiState::Idle, Action::Wait);
+                    }
+                    let path = a_star(actors, map, factions, self.position, *destination, actor_id);
+                    if let Some(path) = path
+                        && !path.is_empty()
+                    {
+                        return (ActorAiState::Wandering(*destination), Self::step_towards(map, path[0]));
+                    }
+                    return (ActorAiState::Idle, Action::Wait);
+                }
+
+                ActorAiState::Sleeping => {
+                    // sleepers are woken by spotting a hostile close by, a loud noise or being hurt
+                    return (ActorAiState::Sleeping, Action::Wait);
+                }
+
+                ActorAiState::Guarding(guard_post) => {
+                    if let Some(target_actor_id) = self.find_target(actor_id, actors, map, factions, &visible_tiles, rng) {
+                        current_state = ActorAiState::TargetingActor(target_actor_id);
+                        continue;
+                    }
+
+                    if &self.position == guard_post {
+                        return (ActorAiState::Guarding(*guard_post), Action::Wait);
+                    }
+                    let path = a_star(actors, map, factions, self.position, *guard_post, actor_id);
+                    if let Some(path) = path
+                        && !path.is_empty()
+                    {
+                        return (ActorAiState::Guarding(*guard_post), Self::step_towards(map, path[0]));
+                    }
+                    return (ActorAiState::Guarding(*guard_post), Action::Wait);
+                }
             }
         }
     }
 
-    pub fn is_friendly_towards(&self, other: &Actor) -> bool {
-        let self_faction = match self.character_stats.as_ref() {
-            Some(stats) => stats.faction.clone(),
+    /// Picks a random visible hostile to go after once alert, only ones near the post for guards.
+    fn find_target(&self, actor_id: usize, actors: &ActorManager, map: &MapManager, factions: &FactionRelations, visible_tiles: &[Position], rng: &mut ChaCha12Rng) -> Option<usize> {
+        if self.awareness != Awareness::Alert {
+            return None;
+        }
+        self.visible_hostiles(actor_id, actors, map, factions, visible_tiles).choose(rng).copied()
+    }
+
+    /// Hostiles standing on the given visible tiles, leaving out ones too far from the post for guards.
+    fn visible_hostiles(&self, actor_id: usize, actors: &ActorManager, map: &MapManager, factions: &FactionRelations, visible_tiles: &[Position]) -> Vec<usize> {
+        let mut hostiles = Vec::new();
+        for visible_position in visible_tiles {
+            if let Some(tile) = map.get_tile(*visible_position)
+                && let Some(other_actor_id) = tile.actor_id()
+                && other_actor_id != actor_id
+                && self.guard_post.is_none_or(|guard_post| visible_position.chebyshev_distance(guard_post) <= GUARD_RADIUS)
+            {
+                let target_actor = actors.get_actor(other_actor_id).unwrap();
+                if self.is_hostile_towards(target_actor, factions) {
+                    hostiles.push(other_actor_id);
+                }
+            }
+        }
+        hostiles
+    }
+
+    /// The free neighbouring tile that gets furthest from a threat, if any gets further than staying put.
+    fn step_away(map: &MapManager, position: Position, threat: Position) -> Option<Position> {
+        let current_distance = position.chebyshev_distance(threat);
+        position
+            .get_neighbours()
+            .into_iter()
+            .map(|(neighbour, _)| neighbour)
+            .filter(|neighbour| map.get_tile(*neighbour).is_some_and(|tile| tile.is_walkable() && tile.actor_id().is_none()))
+            .filter(|neighbour| neighbour.chebyshev_distance(threat) > current_distance)
+            .max_by_key(|neighbour| neighbour.chebyshev_distance(threat))
+    }
+
+    /// Moves onto the next tile of a path, opening it first if it is a closed door.
+    fn step_towards(map: &MapManager, next_position: Position) -> Action {
+        match map.get_tile(next_position).map(|tile| tile.tile_type()) {
+            Some(TileType::ClosedDoor) => Action::Interact(next_position),
+            _ => Action::MoveTo(next_position),
+        }
+    }
+
+    pub fn faction(&self) -> &str {
+        match self.character_stats.as_ref() {
+            Some(stats) => &stats.faction,
             None => match ASSETS.actor_kinds.iter().find(|kind| kind.id == self.kind_id) {
-                Some(kind) => kind.faction.clone(),
-                None => String::new(),
+                Some(kind) => &kind.faction,
+                None => "",
             },
-        };
+        }
+    }
 
-        let other_faction = match other.character_stats.as_ref() {
-            Some(stats) => stats.faction.clone(),
-            None => match ASSETS.actor_kinds.iter().find(|kind| kind.id == other.kind_id) {
-                Some(kind) => kind.faction.clone(),
-                None => String::new(),
-            },
-        };
+    /// How this actor's faction regards the other actor's faction.
+    pub fn relation_towards(&self, other: &Actor, factions: &FactionRelations) -> Relation {
+        factions.relation(self.faction(), other.faction())
+    }
+
+    pub fn is_friendly_towards(&self, other: &Actor, factions: &FactionRelations) -> bool {
+        self.relation_towards(other, factions) == Relation::Allied
+    }
 
-        self_faction == other_faction
+    pub fn is_hostile_towards(&self, other: &Actor, factions: &FactionRelations) -> bool {
+        self.relation_towards(other, factions) == Relation::Hostile
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct CharacterStats {
-    #[allow(dead_code)]
     name: String,
+    class_id: String,
+    attributes: Attributes,
+    mana: i32,
+    /// Action points since mana last regenerated.
+    mana_elapsed: u32,
+    level: u32,
+    experience: u32,
+    unspent_points: i32,
     inventory: Inventory,
     equipment: Equipment,
     faction: String,
 }
 
 impl CharacterStats {
-    pub fn new(name: String, faction: String) -> Self {
+    pub fn new(name: String, class_id: String, faction: String, attributes: Attributes) -> Self {
         Self {
             name,
+            class_id,
+            attributes,
+            mana: 0,
+            mana_elapsed: 0,
+            level: 1,
+            experience: 0,
+            unspent_points: 0,
             inventory: Inventory::new(),
             equipment: Equipment::new(),
             faction,
@@ -405,7 +1166,112 @@ impl CharacterStats {
     }
 }
 
+/// A shot the actor is able to make.
+pub struct RangedAttack {
+    pub damage: Dice,
+    pub damage_type: DamageType,
+    pub range: i64,
+    /// Ammunition used up by the shot, monsters do not need any.
+    pub ammo_id: Option<String>,
+}
+
+/// Total experience needed to reach a level, growing so each level takes longer than the last.
+pub fn experience_for_level(level: u32) -> u32 {
+    10 * level * (level - 1)
+}
+
+/// How an attack roll turned out, with the damage rolled for hits.
+pub enum AttackOutcome {
+    Miss,
+    Hit(i32),
+    Crit(i32),
+}
+
+/// How a hit's damage was changed on its way to the actor's health, for the message log.
+pub struct DamageBreakdown {
+    pub damage_type: DamageType,
+    /// Negative when the actor resisted the damage, positive when vulnerable to it.
+    pub modifier_percent: i32,
+    /// Damage taken off by armor.
+    pub soaked: i32,
+    pub taken: i32,
+    pub result: ApplyDamageResult,
+}
+
+impl DamageBreakdown {
+    /// Reads like "7 fire damage (resisted 50%, 2 soaked)".
+    pub fn describe(&self) -> String {
+        let mut notes = Vec::new();
+        if self.modifier_percent <= -100 {
+            notes.push("immune".to_string());
+        } else if self.modifier_percent < 0 {
+            notes.push(format!("resisted {}%", -self.modifier_percent));
+        } else if self.modifier_percent > 0 {
+            notes.push(format!("vulnerable +{}%", self.modifier_percent));
+        }
+        if self.soaked > 0 {
+            notes.push(format!("{} soaked", self.soaked));
+        }
+
+        let description = format!("{} {} damage", self.taken, self.damage_type.name());
+        if notes.is_empty() { description } else { format!("{} ({})", description, notes.join(", ")) }
+    }
+}
+
 pub enum ApplyDamageResult {
     None,
     ActorDied,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn monster(kind_id: &str) -> Actor {
+        Actor::new(kind_id.to_string(), None, Position { x: 1, y: 1 })
+    }
+
+    fn damage_taken(kind_id: &str, damage: i32, damage_type: DamageType) -> DamageBreakdown {
+        monster(kind_id).apply_damage(damage, damage_type, &mut ChaCha12Rng::seed_from_u64(0))
+    }
+
+    #[test]
+    fn resistances_round_damage_down() {
+        // skeletons resist cold by 50%
+        let breakdown = damage_taken("skeleton", 5, DamageType::Cold);
+        assert_eq!(breakdown.modifier_percent, -50);
+        assert_eq!(breakdown.taken, 2);
+        assert_eq!(breakdown.describe(), "2 cold damage (resisted 50%)");
+
+        // and slashing by 25%, with no natural armor to soak it
+        assert_eq!(damage_taken("skeleton", 7, DamageType::Slash).taken, 5);
+    }
+
+    #[test]
+    fn vulnerabilities_round_damage_down() {
+        // cave spiders take 50% more from fire
+        let breakdown = damage_taken("cave_spider", 5, DamageType::Fire);
+        assert_eq!(breakdown.modifier_percent, 50);
+        assert_eq!(breakdown.taken, 7);
+        assert_eq!(breakdown.describe(), "7 fire damage (vulnerable +50%)");
+    }
+
+    #[test]
+    fn resisted_hits_still_do_one_damage_unless_immune() {
+        assert_eq!(damage_taken("skeleton", 1, DamageType::Cold).taken, 1);
+
+        let breakdown = damage_taken("skeleton", 10, DamageType::Poison);
+        assert_eq!(breakdown.taken, 0);
+        assert_eq!(breakdown.describe(), "0 poison damage (immune)");
+    }
+
+    #[test]
+    fn soak_only_stops_physical_damage() {
+        // cave spiders have a flat soak of 1
+        let breakdown = damage_taken("cave_spider", 4, DamageType::Pierce);
+        assert_eq!((breakdown.soaked, breakdown.taken), (1, 3));
+        let breakdown = damage_taken("cave_spider", 4, DamageType::Cold);
+        assert_eq!((breakdown.soaked, breakdown.taken), (0, 4));
+    }
+}
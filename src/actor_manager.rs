@@ -1,6 +1,8 @@
-use crate::actor::Actor;
+use crate::{actor::Actor, status_effect::StatusTick};
+use serde::{Deserialize, Serialize};
 use std::collections::{BinaryHeap, HashMap};
 
+#[derive(Serialize, Deserialize)]
 pub struct ActorManager {
     actors: HashMap<usize, Actor>,
     turn_queue: BinaryHeap<ActorTurn>,
@@ -13,8 +15,8 @@ impl ActorManager {
         Self {
             actors: HashMap::new(),
             turn_queue: BinaryHeap::new(),
-            current_turn: Some(ActorTurn { actor_id: 0, action_points: 0 }), // start the game on the players turn with 0 action points spent
-            next_id: 0,
+            current_turn: None,
+            next_id: 1, // id 0 is reserved for the player
         }
     }
 
@@ -25,13 +27,20 @@ impl ActorManager {
         self.next_id - 1
     }
 
+    /// Places the player on this level, joining the turn order alongside whoever is due to act next.
+    pub fn add_player_actor(&mut self, actor: Actor) {
+        let action_points = self.turn_queue.peek().map(|turn| turn.action_points).unwrap_or(0);
+        self.actors.insert(0, actor);
+        self.turn_queue.push(ActorTurn::new(0, action_points));
+    }
+
     pub fn remove_actor(&mut self, actor_id: usize) {
         self.actors.remove(&actor_id);
         self.turn_queue.retain(|turn| turn.actor_id != actor_id);
-        if let Some(current) = &self.current_turn {
-            if current.actor_id == actor_id {
-                self.current_turn = None;
-            }
+        if let Some(current) = &self.current_turn
+            && current.actor_id == actor_id
+        {
+            self.current_turn = None;
         }
     }
 
@@ -44,7 +53,7 @@ impl ActorManager {
     }
 
     pub fn get_player_actor(&self) -> &Actor {
-        &self.actors.get(&0).unwrap()
+        self.actors.get(&0).unwrap()
     }
 
     pub fn get_player_actor_mut(&mut self) -> &mut Actor {
@@ -52,7 +61,9 @@ impl ActorManager {
     }
 
     pub fn take_player_actor(&mut self) -> Actor {
-        self.actors.remove(&0).unwrap()
+        let actor = self.actors.remove(&0).unwrap();
+        self.remove_actor(0);
+        actor
     }
 
     pub fn next_turn(&mut self) -> Option<usize> {
@@ -60,23 +71,27 @@ impl ActorManager {
             Some(turn) => {
                 self.current_turn = Some(turn);
                 let actor_id = self.current_turn.as_ref().unwrap().actor_id;
-                return Some(actor_id);
+                Some(actor_id)
             }
             None => unreachable!("Turn queue should never be empty"),
         }
     }
 
-    pub fn end_turn(&mut self, cost: u32) {
+    /// Ends the current actor's turn after an action costing `cost` action points, letting that much time pass for it.
+    pub fn end_turn(&mut self, cost: u32) -> StatusTick {
         match self.current_turn.take() {
             Some(mut turn) => {
                 turn.action_points += cost;
+                let actor_id = turn.actor_id;
                 self.turn_queue.push(turn);
+                self.actors.get_mut(&actor_id).map(|actor| actor.pass_time(cost)).unwrap_or_default()
             }
             None => unreachable!("No current turn to end"),
         }
     }
 }
 
+#[derive(Serialize, Deserialize)]
 struct ActorTurn {
     actor_id: usize,
     action_points: u32,
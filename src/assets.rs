@@ -1,14 +1,20 @@
 use core::panic;
 
+use crate::{attributes::Attributes, dice::Dice};
 use crossterm::style::Color;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
 
 pub static ASSETS: Lazy<Assets> = Lazy::new(|| load_data("assets"));
 
 pub struct Assets {
     pub actor_kinds: Vec<ActorKind>,
     pub item_kinds: Vec<ItemKind>,
+    pub character_classes: Vec<CharacterClass>,
+    pub status_effect_kinds: Vec<StatusEffectKind>,
+    pub spell_kinds: Vec<SpellKind>,
+    pub factions: Vec<FactionKind>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -18,6 +24,14 @@ enum DataType {
     Actor(ActorKind),
     #[serde(rename = "item")]
     Item(ItemKind),
+    #[serde(rename = "class")]
+    Class(CharacterClass),
+    #[serde(rename = "status_effect")]
+    StatusEffect(StatusEffectKind),
+    #[serde(rename = "spell")]
+    Spell(SpellKind),
+    #[serde(rename = "faction")]
+    Faction(FactionKind),
 }
 
 fn serialize_color<S>(color: &Color, serializer: S) -> Result<S::Ok, S::Error>
@@ -85,15 +99,152 @@ pub struct ActorKind {
 
     pub health: i32,
     pub speed: u32,
+    /// Damage of the actor's own attack, used when no weapon is wielded.
+    #[serde(default = "default_unarmed_damage")]
+    pub damage: Dice,
+    #[serde(default)]
+    pub damage_type: DamageType,
+    /// Natural armor taken off every physical hit.
+    #[serde(default)]
+    pub soak: Dice,
+    /// Percentage taken off damage of each type.
+    #[serde(default)]
+    pub resistances: HashMap<DamageType, i32>,
+    /// Percentage added to damage of each type.
+    #[serde(default)]
+    pub vulnerabilities: HashMap<DamageType, i32>,
 
     #[serde(default = "default_true")]
     pub spawnable: bool,
+    /// Offered as a race during character creation.
+    #[serde(default)]
+    pub playable: bool,
+    #[serde(default = "Attributes::average")]
+    pub attributes: Attributes,
+    /// Experience awarded for killing one.
+    #[serde(default)]
+    pub experience: u32,
+    /// Items the actor may drop when it dies, each rolled independently.
+    #[serde(default)]
+    pub loot: Vec<LootEntry>,
+    /// Status effects the actor's melee hits may inflict.
+    #[serde(default)]
+    pub attack_effects: Vec<AttackEffect>,
+    /// Spells the actor casts, monsters do not need mana for them.
+    #[serde(default)]
+    pub spells: Vec<String>,
+    /// Chance each turn that a monster with a target in range casts one of its spells.
+    #[serde(default)]
+    pub spell_chance: f64,
+    /// Monsters with a ranged attack shoot from a distance instead of closing in.
+    #[serde(default)]
+    pub ranged: Option<MonsterRangedAttack>,
+    #[serde(default)]
+    pub behaviour: Behaviour,
+}
+
+/// How a monster acts when it has nothing to fight and when a fight goes badly.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Behaviour {
+    /// Fraction of maximum health below which the monster runs from its target, 0 to never flee.
+    pub flee_below: f64,
+    pub idle: IdleBehaviour,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum IdleBehaviour {
+    /// Waits where it is for something to come along.
+    #[default]
+    #[serde(rename = "stand")]
+    Stand,
+    /// Strolls between nearby spots it can see.
+    #[serde(rename = "wander")]
+    Wander,
+    /// Starts the game asleep and stays that way until disturbed.
+    #[serde(rename = "sleep")]
+    Sleep,
+    /// Keeps to the spot it started on, only chasing what comes close to it.
+    #[serde(rename = "guard")]
+    Guard,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MonsterRangedAttack {
+    pub damage: Dice,
+    #[serde(default = "default_ranged_damage_type")]
+    pub damage_type: DamageType,
+    pub range: i64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LootEntry {
+    pub item: String,
+    #[serde(default = "default_one")]
+    pub quantity: i32,
+    #[serde(default = "default_chance")]
+    pub chance: f64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AttackEffect {
+    pub effect: String,
+    #[serde(default = "default_one")]
+    pub magnitude: i32,
+    /// Duration in turns.
+    pub duration: i32,
+    #[serde(default = "default_chance")]
+    pub chance: f64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CharacterClass {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub health_bonus: i32,
+    /// Added to the race's attributes.
+    #[serde(default)]
+    pub attribute_bonus: Attributes,
+    /// Base mana pool before intelligence.
+    #[serde(default)]
+    pub mana: i32,
+    #[serde(default)]
+    pub spells: Vec<String>,
+    #[serde(default)]
+    pub starting_items: Vec<StartingItem>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StartingItem {
+    pub item: String,
+    #[serde(default = "default_one")]
+    pub quantity: i32,
+    #[serde(default)]
+    pub equipped: bool,
 }
 
 fn default_true() -> bool {
     true
 }
 
+fn default_one() -> i32 {
+    1
+}
+
+fn default_chance() -> f64 {
+    1.0
+}
+
+fn default_unarmed_damage() -> Dice {
+    Dice::new(1, 2, 0)
+}
+
+fn default_ranged_damage_type() -> DamageType {
+    DamageType::Pierce
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ItemKind {
     pub id: String,
@@ -107,16 +258,96 @@ pub struct ItemKind {
 #[serde(tag = "item_type")]
 pub enum ItemType {
     #[serde(rename = "weapon")]
-    Weapon { damage: i32, weapon_type: WeaponType },
+    Weapon {
+        damage: Dice,
+        #[serde(default)]
+        damage_type: DamageType,
+        weapon_type: WeaponType,
+        /// Spells a staff lets its wielder cast.
+        #[serde(default)]
+        spells: Vec<String>,
+        /// Extra damage a staff adds to spells.
+        #[serde(default)]
+        spell_power: i32,
+        /// How far a ranged weapon shoots, 0 for melee weapons.
+        #[serde(default)]
+        range: i64,
+    },
+    /// Fired from a ranged weapon of the matching type, adding its damage to the shot.
+    #[serde(rename = "ammo")]
+    Ammo { damage: i32, weapon_type: WeaponType },
+    /// Takes a roll of its soak off every physical hit the wearer suffers.
     #[serde(rename = "armor")]
-    Armor { defense: i32, slot: ArmorSlot },
+    Armor {
+        soak: Dice,
+        slot: ArmorSlot,
+        #[serde(default)]
+        resistances: HashMap<DamageType, i32>,
+        #[serde(default)]
+        vulnerabilities: HashMap<DamageType, i32>,
+        /// Stealth lost to the weight and clatter of heavy armor.
+        #[serde(default)]
+        stealth_penalty: i32,
+    },
     #[serde(rename = "consumable")]
     Consumable { effect: ConsumableEffect },
     #[serde(rename = "misc")]
     Miscellaneous { stackable: bool },
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+impl ItemType {
+    /// How an item of this type looks when lying on the floor.
+    pub fn glyph(&self) -> (char, Color) {
+        match self {
+            ItemType::Weapon { .. } => (')', Color::Cyan),
+            ItemType::Armor { .. } => ('[', Color::Cyan),
+            ItemType::Ammo { .. } => ('(', Color::Cyan),
+            ItemType::Consumable { .. } => ('!', Color::Magenta),
+            ItemType::Miscellaneous { .. } => ('*', Color::Yellow),
+        }
+    }
+}
+
+/// What a hit is made of, which resistances and vulnerabilities apply to.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DamageType {
+    #[default]
+    #[serde(rename = "blunt")]
+    Blunt,
+    #[serde(rename = "slash")]
+    Slash,
+    #[serde(rename = "pierce")]
+    Pierce,
+    #[serde(rename = "fire")]
+    Fire,
+    #[serde(rename = "cold")]
+    Cold,
+    #[serde(rename = "poison")]
+    Poison,
+    #[serde(rename = "force")]
+    Force,
+}
+
+impl DamageType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DamageType::Blunt => "blunt",
+            DamageType::Slash => "slash",
+            DamageType::Pierce => "pierce",
+            DamageType::Fire => "fire",
+            DamageType::Cold => "cold",
+            DamageType::Poison => "poison",
+            DamageType::Force => "force",
+        }
+    }
+
+    /// Physical damage is what armor soaks.
+    pub fn is_physical(&self) -> bool {
+        matches!(self, DamageType::Blunt | DamageType::Slash | DamageType::Pierce)
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub enum WeaponType {
     #[serde(rename = "sword")]
     Sword,
@@ -148,17 +379,168 @@ pub enum ConsumableEffect {
     Heal { amount: i32 },
     #[serde(rename = "mana")]
     RestoreMana { amount: i32 },
+    /// Applies the status effect with the id `stat` at a magnitude of `amount` for `duration` turns.
     #[serde(rename = "buff")]
     Buff { stat: String, amount: i32, duration: i32 },
+    /// Bursts around the user, hurting everything else within `radius` tiles.
+    #[serde(rename = "burst")]
+    Burst { damage: Dice, damage_type: DamageType, radius: i64 },
+}
+
+/// A timed condition such as poison or haste. Every modifier is multiplied by the magnitude of the active effect.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StatusEffectKind {
+    pub id: String,
+    /// Noun used in the sidebar and causes of death, "Poison".
+    pub name: String,
+    /// Used in messages, "you are poisoned".
+    pub adjective: String,
+    #[serde(serialize_with = "serialize_color", deserialize_with = "deserialize_color")]
+    pub color: Color,
+    #[serde(default)]
+    pub stacking: Stacking,
+
+    /// Health gained each turn, negative for damage.
+    #[serde(default)]
+    pub health_per_turn: i32,
+    /// Percentage change to speed.
+    #[serde(default)]
+    pub speed_percent: i32,
+    #[serde(default)]
+    pub attributes: Attributes,
+    #[serde(default)]
+    pub defense: i32,
+    /// Movement and attacks may go in a random direction.
+    #[serde(default)]
+    pub confuses: bool,
+    /// Sight is reduced to the neighbouring tiles.
+    #[serde(default)]
+    pub blinds: bool,
+}
+
+/// A side actors can belong to, with how it regards the other factions at the start of a game.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FactionKind {
+    pub id: String,
+    /// Used in messages, "the goblins turn hostile".
+    pub name: String,
+    /// Stance towards factions not listed in `relations`.
+    #[serde(default)]
+    pub default_relation: Relation,
+    #[serde(default)]
+    pub relations: HashMap<String, Relation>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Relation {
+    /// Never attacked and never in the way of a fight.
+    #[serde(rename = "allied")]
+    Allied,
+    /// Left alone unless provoked.
+    #[serde(rename = "neutral")]
+    Neutral,
+    #[default]
+    #[serde(rename = "hostile")]
+    Hostile,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SpellKind {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub mana_cost: i32,
+    #[serde(default)]
+    pub target: SpellTarget,
+    /// Furthest a target can be, in tiles.
+    #[serde(default)]
+    pub range: i64,
+    /// Radius around the target that is also hit, 0 for just the target.
+    #[serde(default)]
+    pub area: i64,
+    #[serde(default)]
+    pub damage: Dice,
+    #[serde(default)]
+    pub damage_type: DamageType,
+    #[serde(default)]
+    pub heal: i32,
+    /// Status effect applied to everything hit.
+    #[serde(default)]
+    pub effect: Option<AttackEffect>,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum SpellTarget {
+    /// Aimed at a position within range.
+    #[default]
+    #[serde(rename = "other")]
+    Other,
+    /// Always affects the caster.
+    #[serde(rename = "caster")]
+    Caster,
+}
+
+/// What happens when an effect is applied to an actor that already has it.
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub enum Stacking {
+    /// The stronger magnitude and the longer duration are kept.
+    #[default]
+    #[serde(rename = "refresh")]
+    Refresh,
+    /// Durations add up, the stronger magnitude is kept.
+    #[serde(rename = "extend")]
+    Extend,
+    /// Magnitudes add up, the longer duration is kept.
+    #[serde(rename = "intensify")]
+    Intensify,
+}
+
+/// Display name for an item id, falling back to the id itself for unknown items.
+pub fn item_name(item_id: &str) -> String {
+    match ASSETS.item_kinds.iter().find(|kind| kind.id == item_id) {
+        Some(kind) => kind.name.clone(),
+        None => item_id.to_string(),
+    }
+}
+
+impl Assets {
+    /// Checks for data the game cannot start a run without, so a broken data file is caught on launch rather than
+    /// after the player has been through character creation.
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.actor_kinds.iter().any(|kind| kind.playable) {
+            return Err("No playable race".to_string());
+        }
+        if self.character_classes.is_empty() {
+            return Err("No character class".to_string());
+        }
+        for class in &self.character_classes {
+            for starting_item in &class.starting_items {
+                let item_kind = self.item_kinds.iter().find(|kind| kind.id == starting_item.item).ok_or_else(|| format!("Invalid starting kit for class {}: unknown item {}", class.id, starting_item.item))?;
+                if starting_item.quantity < 1 {
+                    return Err(format!("Invalid starting kit for class {}: {} has quantity {}", class.id, starting_item.item, starting_item.quantity));
+                }
+                if starting_item.equipped && !matches!(item_kind.item_type, ItemType::Weapon { .. } | ItemType::Armor { .. }) {
+                    return Err(format!("Invalid starting kit for class {}: {} cannot be equipped", class.id, starting_item.item));
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 pub fn load_data(path: &str) -> Assets {
     let mut actor_kinds = Vec::new();
     let mut item_kinds = Vec::new();
+    let mut character_classes = Vec::new();
+    let mut status_effect_kinds = Vec::new();
+    let mut spell_kinds = Vec::new();
+    let mut factions = Vec::new();
+
+    // load files in a fixed order so seeded runs pick from the same lists on every machine
+    let mut paths: Vec<_> = std::fs::read_dir(path).unwrap().map(|file| file.unwrap().path()).collect();
+    paths.sort();
 
-    for file in std::fs::read_dir(path).unwrap() {
-        let file = file.unwrap();
-        let path = file.path();
+    for path in paths {
         if path.extension().and_then(|s| s.to_str()) == Some("yaml") {
             let content = std::fs::read_to_string(&path).unwrap();
             let data_types: Vec<DataType> = serde_yaml::from_str(&content).expect("Failed to parse data file");
@@ -166,10 +548,46 @@ pub fn load_data(path: &str) -> Assets {
                 match data_type {
                     DataType::Actor(actor) => actor_kinds.push(actor),
                     DataType::Item(item) => item_kinds.push(item),
+                    DataType::Class(class) => character_classes.push(class),
+                    DataType::StatusEffect(status_effect) => status_effect_kinds.push(status_effect),
+                    DataType::Spell(spell) => spell_kinds.push(spell),
+                    DataType::Faction(faction) => factions.push(faction),
                 }
             }
         }
     }
 
-    Assets { actor_kinds, item_kinds }
+    let assets = Assets {
+        actor_kinds,
+        item_kinds,
+        character_classes,
+        status_effect_kinds,
+        spell_kinds,
+        factions,
+    };
+    if let Err(err) = assets.validate() {
+        panic!("Invalid data files: {}", err);
+    }
+    assets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shipped_data_is_valid() {
+        assert!(load_data("assets").validate().is_ok());
+    }
+
+    #[test]
+    fn starting_kits_must_be_equippable() {
+        let mut assets = load_data("assets");
+        let class: CharacterClass = serde_yaml::from_str("id: alchemist\nname: Alchemist\ndescription: test\nstarting_items:\n  - item: healing_potion\n    equipped: true\n").unwrap();
+        assets.character_classes.push(class);
+        assert_eq!(assets.validate(), Err("Invalid starting kit for class alchemist: healing_potion cannot be equipped".to_string()));
+
+        assets.character_classes.clear();
+        assert_eq!(assets.validate(), Err("No character class".to_string()));
+    }
 }
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+
+/// The score an unremarkable creature has in every attribute.
+pub const AVERAGE_SCORE: i32 = 10;
+const HEALTH_PER_CONSTITUTION: i32 = 5;
+const SPEED_PER_DEXTERITY: i32 = 5;
+const MANA_PER_INTELLIGENCE: i32 = 5;
+
+pub const ATTRIBUTE_NAMES: [&str; 4] = ["Strength", "Dexterity", "Constitution", "Intelligence"];
+
+/// Primary attributes that every derived combat stat is calculated from.
+///
+/// Missing fields default to zero so class bonuses in YAML only list what they change.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Attributes {
+    pub strength: i32,
+    pub dexterity: i32,
+    pub constitution: i32,
+    pub intelligence: i32,
+}
+
+impl Attributes {
+    /// Every attribute at the average score, used for actor kinds that do not list their own.
+    pub fn average() -> Self {
+        Self {
+            strength: AVERAGE_SCORE,
+            dexterity: AVERAGE_SCORE,
+            constitution: AVERAGE_SCORE,
+            intelligence: AVERAGE_SCORE,
+        }
+    }
+
+    /// Attributes by index in the order of `ATTRIBUTE_NAMES`, for menus that step through them.
+    pub fn get(&self, index: usize) -> i32 {
+        match index {
+            0 => self.strength,
+            1 => self.dexterity,
+            2 => self.constitution,
+            3 => self.intelligence,
+            _ => panic!("Invalid attribute index {}", index),
+        }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> &mut i32 {
+        match index {
+            0 => &mut self.strength,
+            1 => &mut self.dexterity,
+            2 => &mut self.constitution,
+            3 => &mut self.intelligence,
+            _ => panic!("Invalid attribute index {}", index),
+        }
+    }
+
+    /// Sum of every attribute, used to count points being spent.
+    pub fn total(&self) -> i32 {
+        self.strength + self.dexterity + self.constitution + self.intelligence
+    }
+
+    /// Extra (or lost) maximum health from constitution.
+    pub fn health_bonus(&self) -> i32 {
+        modifier(self.constitution) * HEALTH_PER_CONSTITUTION
+    }
+
+    /// Bonus to hit from dexterity.
+    pub fn accuracy_bonus(&self) -> i32 {
+        modifier(self.dexterity)
+    }
+
+    /// Bonus melee damage from strength.
+    pub fn damage_bonus(&self) -> i32 {
+        modifier(self.strength)
+    }
+
+    /// Defense from dexterity, on top of worn armor.
+    pub fn defense_bonus(&self) -> i32 {
+        modifier(self.dexterity)
+    }
+
+    /// Stealth from dexterity, before any armor weighs it down.
+    pub fn stealth_bonus(&self) -> i32 {
+        modifier(self.dexterity)
+    }
+
+    /// Extra (or lost) maximum mana from intelligence.
+    pub fn mana_bonus(&self) -> i32 {
+        modifier(self.intelligence) * MANA_PER_INTELLIGENCE
+    }
+
+    /// Bonus spell damage from intelligence.
+    pub fn spell_damage_bonus(&self) -> i32 {
+        modifier(self.intelligence)
+    }
+
+    /// Change to speed from dexterity.
+    pub fn speed_bonus(&self) -> i32 {
+        modifier(self.dexterity) * SPEED_PER_DEXTERITY
+    }
+}
+
+impl std::ops::Add for Attributes {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            strength: self.strength + other.strength,
+            dexterity: self.dexterity + other.dexterity,
+            constitution: self.constitution + other.constitution,
+            intelligence: self.intelligence + other.intelligence,
+        }
+    }
+}
+
+impl std::ops::Mul<i32> for Attributes {
+    type Output = Self;
+
+    fn mul(self, factor: i32) -> Self {
+        Self {
+            strength: self.strength * factor,
+            dexterity: self.dexterity * factor,
+            constitution: self.constitution * factor,
+            intelligence: self.intelligence * factor,
+        }
+    }
+}
+
+/// The bonus an attribute score gives, +1 for every two points above average and -1 for every two below.
+pub fn modifier(score: i32) -> i32 {
+    (score - AVERAGE_SCORE).div_euclid(2)
+}
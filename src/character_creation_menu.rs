@@ -1,44 +1,254 @@
-use crate::{GameData, GameState, main_menu::MainMenu, playing::Playing};
+use crate::{
+    GameData, GameState,
+    actor::{Actor, CharacterStats},
+    assets::{ASSETS, ActorKind, CharacterClass, item_name},
+    attributes::{ATTRIBUTE_NAMES, Attributes},
+    main_menu::MainMenu,
+    playing::Playing,
+    position::Position,
+};
 use crossterm::{
     QueueableCommand,
     cursor::MoveTo,
     event::KeyCode,
-    style::Print,
+    style::{Color, Print, PrintStyledContent, Stylize, style},
     terminal::{Clear, ClearType},
 };
 use std::io::Write;
 
-// ActorKind (race) > class? > stats > name > confirm
+const MAX_NAME_LENGTH: usize = 16;
+/// Points the player distributes over their attributes on top of race and class.
+const STARTING_ATTRIBUTE_POINTS: i32 = 4;
+
+/// The steps of character creation, in the order they are visited.
+#[derive(Clone, Copy, PartialEq)]
+enum Step {
+    Race,
+    Class,
+    Stats,
+    Name,
+    Confirm,
+}
 
 pub struct CharacterCreationMenu {
-    // cursor: u8,
-    // step: u8,
+    step: Step,
+    cursor: usize,
 
-    // actor_kind: String,
+    race: Option<&'static ActorKind>,
+    class: Option<&'static CharacterClass>,
+    /// Points spent on each attribute during the stats step.
+    spent_points: Attributes,
+    name: String,
 }
 
 impl CharacterCreationMenu {
     pub fn new() -> Self {
         Self {
-            // cursor: 0,
-            // step: 0,
+            step: Step::Race,
+            cursor: 0,
+
+            race: None,
+            class: None,
+            spent_points: Attributes::default(),
+            name: String::new(),
+        }
+    }
+
+    pub fn update(mut self, game_data: &mut GameData) -> GameState {
+        let key = game_data.input.last_key();
+        match self.step {
+            Step::Race => {
+                let races = playable_races();
+                match key {
+                    KeyCode::Esc => return GameState::MainMenu(MainMenu::new()),
+                    KeyCode::Enter => {
+                        self.race = Some(races[self.cursor]);
+                        self.step = Step::Class;
+                        self.cursor = 0;
+                    }
+                    _ => self.move_cursor(key, races.len()),
+                }
+            }
+            Step::Class => match key {
+                KeyCode::Esc => {
+                    self.step = Step::Race;
+                    self.cursor = playable_races().iter().position(|race| self.race.is_some_and(|chosen| chosen.id == race.id)).unwrap_or(0);
+                }
+                KeyCode::Enter => {
+                    self.class = Some(&ASSETS.character_classes[self.cursor]);
+                    self.spent_points = Attributes::default();
+                    self.step = Step::Stats;
+                    self.cursor = 0;
+                }
+                _ => self.move_cursor(key, ASSETS.character_classes.len()),
+            },
+            Step::Stats => match key {
+                KeyCode::Esc => {
+                    self.step = Step::Class;
+                    self.cursor = ASSETS.character_classes.iter().position(|class| self.class.is_some_and(|chosen| chosen.id == class.id)).unwrap_or(0);
+                }
+                KeyCode::Enter if self.remaining_points() == 0 => self.step = Step::Name,
+                KeyCode::Char('d') | KeyCode::Right | KeyCode::Char('+') if self.remaining_points() > 0 => *self.spent_points.get_mut(self.cursor) += 1,
+                KeyCode::Char('a') | KeyCode::Left | KeyCode::Char('-') if self.spent_points.get(self.cursor) > 0 => *self.spent_points.get_mut(self.cursor) -= 1,
+                _ => self.move_cursor(key, ATTRIBUTE_NAMES.len()),
+            },
+            Step::Name => match key {
+                KeyCode::Esc => self.step = Step::Stats,
+                KeyCode::Enter if !self.name.trim().is_empty() => {
+                    self.name = self.name.trim().to_string();
+                    self.step = Step::Confirm;
+                }
+                KeyCode::Backspace => {
+                    self.name.pop();
+                }
+                KeyCode::Char(c) if !c.is_control() && self.name.chars().count() < MAX_NAME_LENGTH => self.name.push(c),
+                _ => {}
+            },
+            Step::Confirm => match key {
+                KeyCode::Esc => self.step = Step::Name,
+                KeyCode::Enter => {
+                    let player = self.build_player();
+                    game_data.new_game(player);
+                    return GameState::Playing(Playing::new());
+                }
+                _ => {}
+            },
         }
+
+        GameState::CharacterCreationMenu(self)
     }
 
-    pub fn update(self, game_data: &mut GameData) -> GameState {
-        match game_data.input.last_key() {
-            KeyCode::Esc => return GameState::MainMenu(MainMenu::new()),
-            KeyCode::Enter => return GameState::Playing(Playing::new()),
-            _ => GameState::CharacterCreationMenu(self),
+    fn move_cursor(&mut self, key: KeyCode, len: usize) {
+        if len == 0 {
+            return;
+        }
+        match key {
+            KeyCode::Char('w') | KeyCode::Up => self.cursor = if self.cursor > 0 { self.cursor - 1 } else { len - 1 },
+            KeyCode::Char('s') | KeyCode::Down => self.cursor = if self.cursor < len - 1 { self.cursor + 1 } else { 0 },
+            _ => {}
         }
     }
 
+    fn remaining_points(&self) -> i32 {
+        STARTING_ATTRIBUTE_POINTS - self.spent_points.total()
+    }
+
+    /// Race attributes plus the class bonus and any points spent so far.
+    fn attributes(&self) -> Attributes {
+        self.race.unwrap().attributes + self.class.unwrap().attribute_bonus + self.spent_points
+    }
+
+    /// Builds the player character from the choices made, with the class starting kit handed out.
+    fn build_player(&self) -> Actor {
+        let race = self.race.unwrap();
+        let class = self.class.unwrap();
+
+        let character_stats = CharacterStats::new(self.name.clone(), class.id.clone(), "player".to_string(), self.attributes());
+        let mut player = Actor::new(race.id.clone(), Some(character_stats), Position { x: 0, y: 0 });
+        player.add_starting_items().expect("starting kits are validated when the assets load");
+        player
+    }
+
     pub fn draw(&self) -> std::io::Result<()> {
         let mut stdout = std::io::stdout();
         stdout.queue(Clear(ClearType::All))?;
-        stdout.queue(MoveTo(0, 0))?;
-        stdout.queue(Print("Character Creation"))?;
+        stdout.queue(MoveTo(2, 1))?;
+        stdout.queue(PrintStyledContent(style("Character Creation").with(Color::Yellow)))?;
+
+        let mut lines: Vec<(String, Color)> = Vec::new();
+        match self.step {
+            Step::Race => {
+                lines.push(("Choose your race:".to_string(), Color::White));
+                lines.push((String::new(), Color::White));
+                for (i, race) in playable_races().iter().enumerate() {
+                    let color = if i == self.cursor { Color::Red } else { Color::White };
+                    lines.push((format!("{:<10} Health {:<4} Speed {}", race.name, race.health, race.speed), color));
+                }
+            }
+            Step::Class => {
+                lines.push(("Choose your class:".to_string(), Color::White));
+                lines.push((String::new(), Color::White));
+                for (i, class) in ASSETS.character_classes.iter().enumerate() {
+                    let color = if i == self.cursor { Color::Red } else { Color::White };
+                    lines.push((format!("{:<10} {}", class.name, class.description), color));
+                }
+
+                let class = &ASSETS.character_classes[self.cursor];
+                lines.push((String::new(), Color::White));
+                lines.push((format!("Health bonus: +{}", class.health_bonus), Color::DarkGrey));
+                lines.push((format!("Starting kit: {}", describe_kit(class)), Color::DarkGrey));
+            }
+            Step::Stats => {
+                lines.push((format!("Distribute your attribute points ({} remaining):", self.remaining_points()), Color::White));
+                lines.push((String::new(), Color::White));
+                let attributes = self.attributes();
+                for (i, attribute_name) in ATTRIBUTE_NAMES.iter().enumerate() {
+                    let color = if i == self.cursor { Color::Red } else { Color::White };
+                    let spent = self.spent_points.get(i);
+                    let spent = if spent > 0 { format!("(+{})", spent) } else { String::new() };
+                    lines.push((format!("{:<14} {:>3} {}", attribute_name, attributes.get(i), spent), color));
+                }
+                lines.push((String::new(), Color::White));
+                lines.push(("Constitution adds health, strength adds damage,".to_string(), Color::DarkGrey));
+                lines.push(("dexterity adds accuracy, defense and speed.".to_string(), Color::DarkGrey));
+            }
+            Step::Name => {
+                lines.push(("Enter your name:".to_string(), Color::White));
+                lines.push((String::new(), Color::White));
+                lines.push((format!("> {}_", self.name), Color::Red));
+            }
+            Step::Confirm => {
+                let player = self.build_player();
+                let attributes = player.attributes();
+                lines.push((format!("{}, the {}", player.name(), player.title()), Color::White));
+                lines.push((String::new(), Color::White));
+                lines.push((format!("Str {}  Dex {}  Con {}  Int {}", attributes.strength, attributes.dexterity, attributes.constitution, attributes.intelligence), Color::White));
+                lines.push((format!("Health {}  Speed {}", player.max_health(), player.speed()), Color::White));
+                let (min_soak, max_soak) = player.soak_range();
+                let (damage, damage_type) = player.melee_damage();
+                lines.push((format!("Damage {} {}  To hit {:+}  Evasion {:+}  Armor {}-{}", damage, damage_type.name(), player.accuracy_bonus(), player.evasion(), min_soak, max_soak), Color::White));
+                lines.push((format!("Starting kit: {}", describe_kit(self.class.unwrap())), Color::White));
+                lines.push((String::new(), Color::White));
+                lines.push(("Press Enter to begin your descent.".to_string(), Color::Yellow));
+            }
+        }
+
+        for (i, (line, color)) in lines.iter().enumerate() {
+            stdout.queue(MoveTo(4, 3 + i as u16))?;
+            stdout.queue(PrintStyledContent(style(line).with(*color)))?;
+        }
+
+        let (_, height) = crossterm::terminal::size()?;
+        let hint = match self.step {
+            Step::Race => "up/down to choose, Enter to continue, Esc to return to the menu",
+            Step::Stats => "up/down to choose, left/right to spend points, Enter once all are spent, Esc to go back",
+            Step::Name => "type a name, Enter to continue, Esc to go back",
+            _ => "up/down to choose, Enter to continue, Esc to go back",
+        };
+        stdout.queue(MoveTo(2, height.saturating_sub(2)))?;
+        stdout.queue(Print(hint))?;
+
         stdout.flush()?;
         Ok(())
     }
 }
+
+fn playable_races() -> Vec<&'static ActorKind> {
+    ASSETS.actor_kinds.iter().filter(|kind| kind.playable).collect()
+}
+
+fn describe_kit(class: &CharacterClass) -> String {
+    class
+        .starting_items
+        .iter()
+        .map(|starting_item| {
+            let name = item_name(&starting_item.item);
+            match (starting_item.quantity, starting_item.equipped) {
+                (1, true) => format!("{} (equipped)", name),
+                (1, false) => name,
+                (quantity, _) => format!("{} x{}", name, quantity),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
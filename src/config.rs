@@ -0,0 +1,36 @@
+use crate::map_generator::GeneratorKind;
+
+/// Options chosen on the command line when the game is launched.
+pub struct Config {
+    /// Forces every floor to use this generator instead of picking one by depth.
+    pub generator: Option<GeneratorKind>,
+    /// Seeds every new game so runs can be replayed exactly.
+    pub seed: Option<u64>,
+}
+
+impl Config {
+    pub fn from_args() -> Result<Self, String> {
+        let mut config = Self { generator: None, seed: None };
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--generator" => {
+                    let name = args.next().ok_or_else(|| "--generator requires a value".to_string())?;
+                    config.generator = Some(GeneratorKind::from_name(&name).ok_or_else(|| format!("Unknown map generator: {}", name))?);
+                }
+                "--seed" => {
+                    let value = args.next().ok_or_else(|| "--seed requires a value".to_string())?;
+                    config.seed = Some(value.parse().map_err(|_| format!("Invalid seed: {}", value))?);
+                }
+                _ => return Err(format!("Unknown argument: {}", arg)),
+            }
+        }
+
+        Ok(config)
+    }
+
+    pub fn generator_for_depth(&self, depth: usize) -> GeneratorKind {
+        self.generator.unwrap_or_else(|| GeneratorKind::for_depth(depth))
+    }
+}
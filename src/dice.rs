@@ -0,0 +1,134 @@
+use rand::Rng;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// A range of values written as dice, such as "1d8+2", or a flat number like "3".
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Dice {
+    pub count: u32,
+    pub sides: u32,
+    pub bonus: i32,
+}
+
+impl Dice {
+    pub fn new(count: u32, sides: u32, bonus: i32) -> Self {
+        Self { count, sides, bonus }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let Some((count, rest)) = text.split_once('d') else {
+            return text.parse().map(|bonus| Self::new(0, 0, bonus)).map_err(|_| format!("Invalid dice: {}", text));
+        };
+
+        let (sides, bonus) = match rest.find(['+', '-']) {
+            Some(index) => (&rest[..index], rest[index..].trim_start_matches('+')),
+            None => (rest, "0"),
+        };
+        let count = if count.is_empty() { Ok(1) } else { count.parse() };
+        match (count, sides.parse(), bonus.parse()) {
+            (Ok(count), Ok(sides), Ok(bonus)) if sides > 0 => Ok(Self::new(count, sides, bonus)),
+            _ => Err(format!("Invalid dice: {}", text)),
+        }
+    }
+
+    /// The same dice with a flat amount added.
+    pub fn plus(self, bonus: i32) -> Self {
+        Self { bonus: self.bonus + bonus, ..self }
+    }
+
+    /// Rolls the dice, never going below zero.
+    pub fn roll(&self, rng: &mut ChaCha12Rng) -> i32 {
+        let total: i32 = (0..self.count).map(|_| rng.random_range(1..=self.sides) as i32).sum();
+        (total + self.bonus).max(0)
+    }
+
+    pub fn min(&self) -> i32 {
+        (self.count as i32 + self.bonus).max(0)
+    }
+
+    pub fn max(&self) -> i32 {
+        (self.count as i32 * self.sides as i32 + self.bonus).max(0)
+    }
+}
+
+impl fmt::Display for Dice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.count, self.bonus) {
+            (0, bonus) => write!(f, "{}", bonus),
+            (count, 0) => write!(f, "{}d{}", count, self.sides),
+            (count, bonus) => write!(f, "{}d{}{:+}", count, self.sides, bonus),
+        }
+    }
+}
+
+impl Serialize for Dice {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Dice {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // plain numbers in the YAML are read as flat amounts
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum DiceText {
+            Flat(i32),
+            Text(String),
+        }
+
+        match DiceText::deserialize(deserializer)? {
+            DiceText::Flat(bonus) => Ok(Self::new(0, 0, bonus)),
+            DiceText::Text(text) => Self::parse(&text).map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn parses_dice_and_flat_amounts() {
+        assert_eq!(Dice::parse("1d8+2"), Ok(Dice::new(1, 8, 2)));
+        assert_eq!(Dice::parse("d6"), Ok(Dice::new(1, 6, 0)));
+        assert_eq!(Dice::parse("2d4-1"), Ok(Dice::new(2, 4, -1)));
+        assert_eq!(Dice::parse(" 3 "), Ok(Dice::new(0, 0, 3)));
+    }
+
+    #[test]
+    fn rejects_malformed_dice() {
+        for text in ["", "d", "1d", "1d0", "xd6", "1d6+", "1d6+x", "2d6d6", "fire"] {
+            assert!(Dice::parse(text).is_err(), "{:?} should not parse", text);
+        }
+    }
+
+    #[test]
+    fn displays_as_parsed() {
+        for text in ["1d8+2", "1d6", "2d4-1", "3"] {
+            assert_eq!(Dice::parse(text).unwrap().to_string(), text);
+        }
+    }
+
+    #[test]
+    fn rolls_stay_within_range() {
+        let mut rng = ChaCha12Rng::seed_from_u64(1);
+        let dice = Dice::new(2, 6, 1);
+        for _ in 0..200 {
+            let roll = dice.roll(&mut rng);
+            assert!((dice.min()..=dice.max()).contains(&roll));
+        }
+        // a penalty never takes a roll below zero
+        assert_eq!(Dice::new(1, 4, -10).roll(&mut rng), 0);
+    }
+
+    #[test]
+    fn reads_numbers_and_strings_from_yaml() {
+        assert_eq!(serde_yaml::from_str::<Dice>("4").unwrap(), Dice::new(0, 0, 4));
+        assert_eq!(serde_yaml::from_str::<Dice>("1d8+2").unwrap(), Dice::new(1, 8, 2));
+        assert!(serde_yaml::from_str::<Dice>("bad").is_err());
+    }
+}
@@ -0,0 +1,73 @@
+use crate::assets::{ASSETS, Relation};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How the factions regard each other, starting from the data files and changing as the game goes on.
+#[derive(Serialize, Deserialize)]
+pub struct FactionRelations {
+    /// Relations that have changed during the game, by faction and then the faction it regards.
+    changed: HashMap<String, HashMap<String, Relation>>,
+}
+
+impl FactionRelations {
+    pub fn new() -> Self {
+        Self { changed: HashMap::new() }
+    }
+
+    /// How the faction `from` regards the faction `to`. A faction is allied with itself unless its data says otherwise.
+    pub fn relation(&self, from: &str, to: &str) -> Relation {
+        if let Some(relation) = self.changed.get(from).and_then(|relations| relations.get(to)) {
+            return *relation;
+        }
+        let Some(faction) = ASSETS.factions.iter().find(|faction| faction.id == from) else {
+            return if from == to { Relation::Allied } else { Relation::Hostile };
+        };
+        match faction.relations.get(to) {
+            Some(relation) => *relation,
+            None if from == to => Relation::Allied,
+            None => faction.default_relation,
+        }
+    }
+
+    /// Sets how two factions regard each other, both ways.
+    pub fn set_mutual_relation(&mut self, first: &str, second: &str, relation: Relation) {
+        self.changed.entry(first.to_string()).or_default().insert(second.to_string(), relation);
+        self.changed.entry(second.to_string()).or_default().insert(first.to_string(), relation);
+    }
+}
+
+/// The display name of a faction, falling back to its id.
+pub fn faction_name(faction_id: &str) -> String {
+    match ASSETS.factions.iter().find(|faction| faction.id == faction_id) {
+        Some(faction) => faction.name.clone(),
+        None => faction_id.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relations_come_from_the_data() {
+        let factions = FactionRelations::new();
+        assert_eq!(factions.relation("player", "vermin"), Relation::Neutral);
+        // vermin regard everyone as neutral by default, except the undead
+        assert_eq!(factions.relation("vermin", "player"), Relation::Neutral);
+        assert_eq!(factions.relation("vermin", "undead"), Relation::Hostile);
+        assert_eq!(factions.relation("goblin", "player"), Relation::Hostile);
+        assert_eq!(factions.relation("goblin", "goblin"), Relation::Allied);
+        assert_eq!(factions.relation("unknown", "unknown"), Relation::Allied);
+        assert_eq!(factions.relation("unknown", "player"), Relation::Hostile);
+    }
+
+    #[test]
+    fn changed_relations_apply_both_ways_and_only_to_the_pair() {
+        let mut factions = FactionRelations::new();
+        factions.set_mutual_relation("player", "vermin", Relation::Hostile);
+        assert_eq!(factions.relation("player", "vermin"), Relation::Hostile);
+        assert_eq!(factions.relation("vermin", "player"), Relation::Hostile);
+        assert_eq!(factions.relation("goblin", "vermin"), Relation::Neutral);
+        assert_eq!(factions.relation("vermin", "goblin"), Relation::Neutral);
+    }
+}
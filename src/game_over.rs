@@ -0,0 +1,64 @@
+use crate::{GameData, GameState, main_menu::MainMenu};
+use crossterm::{
+    QueueableCommand,
+    cursor::MoveTo,
+    event::KeyCode,
+    style::{Attribute, Color, Print, PrintStyledContent, Stylize, style},
+    terminal::{Clear, ClearType},
+};
+use std::io::Write;
+
+pub struct GameOver {
+    name: String,
+    cause_of_death: String,
+    depth: usize,
+    turns: u64,
+    kills: u32,
+    morgue_result: Result<String, String>,
+}
+
+impl GameOver {
+    pub fn new(game_data: &GameData, morgue_result: Result<String, String>) -> Self {
+        Self {
+            name: game_data.actors.get_player_actor().name(),
+            cause_of_death: game_data.cause_of_death.clone().unwrap_or_else(|| "Died".to_string()),
+            depth: game_data.depth,
+            turns: game_data.turns,
+            kills: game_data.kills,
+            morgue_result,
+        }
+    }
+
+    pub fn update(self, game_data: &mut GameData) -> GameState {
+        match game_data.input.last_key() {
+            KeyCode::Enter | KeyCode::Esc => GameState::MainMenu(MainMenu::new()),
+            _ => GameState::GameOver(self),
+        }
+    }
+
+    pub fn draw(&self) -> std::io::Result<()> {
+        let mut stdout = std::io::stdout();
+        stdout.queue(Clear(ClearType::All))?;
+
+        stdout.queue(MoveTo(4, 2))?;
+        stdout.queue(PrintStyledContent(style("You have died").with(Color::Red).attribute(Attribute::Bold)))?;
+
+        let lines = [self.name.clone(), format!("{} on depth {}", self.cause_of_death, self.depth), format!("Survived {} turns", self.turns), format!("Killed {} monsters", self.kills)];
+        for (i, line) in lines.iter().enumerate() {
+            stdout.queue(MoveTo(4, 4 + i as u16))?;
+            stdout.queue(Print(line))?;
+        }
+
+        stdout.queue(MoveTo(4, 9))?;
+        match &self.morgue_result {
+            Ok(path) => stdout.queue(Print(format!("Morgue file written to {}", path)))?,
+            Err(err) => stdout.queue(PrintStyledContent(style(err).with(Color::Yellow)))?,
+        };
+
+        stdout.queue(MoveTo(4, 11))?;
+        stdout.queue(PrintStyledContent(style("Press Enter to return to the main menu").with(Color::DarkGrey)))?;
+
+        stdout.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{GameData, actor::experience_for_level, assets::item_name};
+use crossterm::{
+    QueueableCommand,
+    cursor::MoveTo,
+    style::{Color, Print, PrintStyledContent, Stylize, style},
+    terminal::{Clear, ClearType},
+};
+use std::io::Stdout;
+
+pub const SIDEBAR_WIDTH: u16 = 30;
+const BAR_WIDTH: i32 = 12;
+
+/// Draws the character panel down the right hand side of the screen, starting at column `x`.
+pub fn draw_sidebar(stdout: &mut Stdout, game_data: &GameData, x: u16, height: u16) -> std::io::Result<()> {
+    let player = game_data.actors.get_player_actor();
+    let mut lines: Vec<Vec<(String, Color)>> = Vec::new();
+
+    lines.push(vec![(player.name(), Color::White)]);
+    lines.push(vec![(player.title(), Color::Grey)]);
+    lines.push(bar("HP", player.health(), player.max_health(), None));
+    if player.max_mana() > 0 {
+        lines.push(bar("MP", player.mana(), player.max_mana(), Some(Color::Blue)));
+    }
+    for effect in player.status_effects() {
+        let kind = effect.kind();
+        let magnitude = if effect.magnitude() > 1 { format!(" x{}", effect.magnitude()) } else { String::new() };
+        lines.push(vec![(format!("{}{} ({})", kind.name, magnitude, effect.remaining_turns()), kind.color)]);
+    }
+    let attributes = player.attributes();
+    lines.push(vec![(format!("Str {} Dex {} Con {} Int {}", attributes.strength, attributes.dexterity, attributes.constitution, attributes.intelligence), Color::Grey)]);
+    let (min_soak, max_soak) = player.soak_range();
+    let (damage, damage_type) = player.melee_damage();
+    lines.push(vec![(format!("Attack {} {}  Hit {:+}", damage, damage_type.name(), player.accuracy_bonus()), Color::Grey)]);
+    lines.push(vec![(format!("Evasion {:+}  Armor {}-{}", player.evasion(), min_soak, max_soak), Color::Grey)]);
+    lines.push(vec![(format!("Stealth {:+}", player.stealth()), Color::Grey)]);
+    lines.push(vec![(format!("Level {}  XP {}/{}", player.level(), player.experience(), experience_for_level(player.level() + 1)), Color::Grey)]);
+    lines.push(vec![(format!("Depth {}  Turn {}", game_data.depth, game_data.turns), Color::Grey)]);
+    lines.push(vec![(format!("Seed {}", game_data.seed), Color::DarkGrey)]);
+    lines.push(Vec::new());
+
+    lines.push(vec![("Equipment".to_string(), Color::Yellow)]);
+    if let Some(equipment) = player.equipment() {
+        for (slot, item_id) in equipment.iter_slots() {
+            let (item, color) = match item_id {
+                Some(item_id) => (item_name(item_id), Color::Grey),
+                None => ("-".to_string(), Color::DarkGrey),
+            };
+            lines.push(vec![(format!("{:<8}", slot), Color::DarkGrey), (item, color)]);
+        }
+    }
+    lines.push(Vec::new());
+
+    lines.push(vec![("In view".to_string(), Color::Yellow)]);
+    let player_position = player.position();
+    let mut visible_actors: Vec<_> = game_data.map.visible_actor_ids().into_iter().filter(|actor_id| *actor_id != 0).filter_map(|actor_id| game_data.actors.get_actor(actor_id)).collect();
+    visible_actors.sort_by(|a, b| a.position().octile_distance(player_position).total_cmp(&b.position().octile_distance(player_position)));
+    for actor in visible_actors {
+        let (glyph, color) = actor.glyph();
+        let mut line = vec![(format!("{} ", glyph), color)];
+        line.extend(bar(&actor.name(), actor.health(), actor.max_health(), None));
+        lines.push(line);
+    }
+
+    for row in 0..height {
+        stdout.queue(MoveTo(x, row))?;
+        stdout.queue(Clear(ClearType::UntilNewLine))?;
+        stdout.queue(Print(" "))?;
+
+        let Some(line) = lines.get(row as usize) else {
+            continue;
+        };
+        let mut remaining = SIDEBAR_WIDTH.saturating_sub(1) as usize;
+        for (text, color) in line {
+            let text: String = text.chars().take(remaining).collect();
+            remaining -= text.chars().count();
+            stdout.queue(PrintStyledContent(style(text).with(*color)))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// A labelled bar such as `HP [######----] 60/100`, coloured by how full it is unless given a `fill_color`.
+fn bar(label: &str, current: i32, max: i32, fill_color: Option<Color>) -> Vec<(String, Color)> {
+    let max = max.max(1);
+    let filled = (current.clamp(0, max) * BAR_WIDTH / max) as usize;
+    let color = fill_color.unwrap_or(match current * 100 / max {
+        0..=25 => Color::Red,
+        26..=60 => Color::Yellow,
+        _ => Color::Green,
+    });
+
+    vec![(format!("{} ", label), Color::Grey), ("#".repeat(filled), color), ("-".repeat(BAR_WIDTH as usize - filled), Color::DarkGrey), (format!(" {}/{}", current, max), Color::Grey)]
+}
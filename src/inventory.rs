@@ -1,11 +1,13 @@
 use crate::assets::{ArmorSlot, ItemKind, ItemType};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct InventoryItem {
     pub item_id: String,
     pub quantity: i32,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct Inventory {
     items: Vec<InventoryItem>,
 }
@@ -48,8 +50,18 @@ impl Inventory {
     pub fn items(&self) -> &[InventoryItem] {
         &self.items
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Empties the inventory, handing back everything that was in it.
+    pub fn take_all(&mut self) -> Vec<InventoryItem> {
+        std::mem::take(&mut self.items)
+    }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct Equipment {
     pub weapon: Option<String>,
     pub offhand: Option<String>,
@@ -74,11 +86,7 @@ impl Equipment {
     }
 
     pub fn can_equip(&self, item: &ItemKind) -> bool {
-        match &item.item_type {
-            ItemType::Weapon { .. } => true,
-            ItemType::Armor { .. } => true,
-            _ => false,
-        }
+        matches!(&item.item_type, ItemType::Weapon { .. } | ItemType::Armor { .. })
     }
 
     pub fn equip_item(&mut self, item: &ItemKind) -> Result<Option<String>, String> {
@@ -118,6 +126,11 @@ impl Equipment {
         self.weapon.take()
     }
 
+    /// Strips every slot, handing back the ids of everything that was equipped.
+    pub fn take_all(&mut self) -> Vec<String> {
+        [&mut self.weapon, &mut self.offhand, &mut self.head, &mut self.chest, &mut self.legs, &mut self.feet, &mut self.hands].into_iter().filter_map(|slot| slot.take()).collect()
+    }
+
     // For UI display, you can still iterate in order:
     pub fn iter_slots(&self) -> Vec<(&str, &Option<String>)> {
         vec![("Weapon", &self.weapon), ("Offhand", &self.offhand), ("Head", &self.head), ("Chest", &self.chest), ("Legs", &self.legs), ("Feet", &self.feet), ("Hands", &self.hands)]
@@ -0,0 +1,174 @@
+use crate::{
+    GameData, GameState,
+    assets::{ASSETS, ArmorSlot, ItemType, item_name},
+    playing::{Action, Playing},
+};
+use crossterm::{
+    QueueableCommand,
+    cursor::MoveTo,
+    event::KeyCode,
+    style::{Color, Print, PrintStyledContent, Stylize, style},
+    terminal::{Clear, ClearType},
+};
+use std::io::Write;
+
+/// Armor slots in the order `Equipment::iter_slots` lists them, the weapon slot being `None`.
+const EQUIPMENT_SLOTS: [Option<ArmorSlot>; 7] = [None, Some(ArmorSlot::Offhand), Some(ArmorSlot::Head), Some(ArmorSlot::Chest), Some(ArmorSlot::Legs), Some(ArmorSlot::Feet), Some(ArmorSlot::Hands)];
+
+enum Selection {
+    None,
+    /// Index into the inventory, chosen with a lowercase letter.
+    Item(usize),
+    /// Index into the equipment slots, chosen with an uppercase letter.
+    Slot(usize),
+}
+
+pub struct InventoryMenu {
+    selection: Selection,
+}
+
+impl InventoryMenu {
+    pub fn new() -> Self {
+        Self { selection: Selection::None }
+    }
+
+    pub fn update(mut self, game_data: &mut GameData) -> GameState {
+        let player = game_data.actors.get_player_actor();
+        let key = game_data.input.last_key();
+
+        let action = match self.selection {
+            Selection::None => match key {
+                KeyCode::Esc | KeyCode::Char('i') => return GameState::Playing(Playing::new()),
+                KeyCode::Char(c @ 'a'..='z') => {
+                    let index = (c as u8 - b'a') as usize;
+                    if player.inventory().is_some_and(|inventory| index < inventory.items().len()) {
+                        self.selection = Selection::Item(index);
+                    }
+                    None
+                }
+                KeyCode::Char(c @ 'A'..='Z') => {
+                    let index = (c as u8 - b'A') as usize;
+                    if index < EQUIPMENT_SLOTS.len() {
+                        self.selection = Selection::Slot(index);
+                    }
+                    None
+                }
+                _ => None,
+            },
+            Selection::Item(index) => {
+                let item_id = player.inventory().unwrap().items()[index].item_id.clone();
+                // only offer what `draw` lists for the item, so a stray key never spends a turn
+                let item_type = ASSETS.item_kinds.iter().find(|kind| kind.id == item_id).map(|kind| &kind.item_type);
+                match key {
+                    KeyCode::Esc => {
+                        self.selection = Selection::None;
+                        None
+                    }
+                    KeyCode::Char('e') if matches!(item_type, Some(ItemType::Weapon { .. } | ItemType::Armor { .. })) => Some(Action::Equip(item_id)),
+                    KeyCode::Char('q') if matches!(item_type, Some(ItemType::Consumable { .. })) => Some(Action::UseItem(item_id)),
+                    KeyCode::Char('d') => Some(Action::Drop(item_id)),
+                    _ => None,
+                }
+            }
+            Selection::Slot(index) => match key {
+                KeyCode::Esc => {
+                    self.selection = Selection::None;
+                    None
+                }
+                KeyCode::Char('r') if player.equipment().is_some_and(|equipment| equipment.iter_slots()[index].1.is_some()) => match &EQUIPMENT_SLOTS[index] {
+                    Some(slot) => Some(Action::UnequipArmor(slot.clone())),
+                    None => Some(Action::UnequipWeapon),
+                },
+                _ => None,
+            },
+        };
+
+        // acting spends game time, after which the player is returned to the map
+        match action {
+            Some(action) => Playing::new().take_player_turn(action, game_data),
+            None => GameState::InventoryMenu(self),
+        }
+    }
+
+    pub fn draw(&self, game_data: &GameData) -> std::io::Result<()> {
+        let mut stdout = std::io::stdout();
+        stdout.queue(Clear(ClearType::All))?;
+
+        let player = game_data.actors.get_player_actor();
+        let (width, _) = crossterm::terminal::size()?;
+        let equipment_x = (width / 2).max(40);
+
+        stdout.queue(MoveTo(0, 0))?;
+        stdout.queue(PrintStyledContent(style("Inventory").with(Color::Yellow)))?;
+        let items = player.inventory().map(|inventory| inventory.items()).unwrap_or(&[]);
+        if items.is_empty() {
+            stdout.queue(MoveTo(2, 2))?;
+            stdout.queue(PrintStyledContent(style("(empty)").with(Color::DarkGrey)))?;
+        }
+        for (i, item) in items.iter().enumerate() {
+            let letter = (b'a' + i as u8) as char;
+            let text = if item.quantity > 1 {
+                format!("{}) {} x{}", letter, item_name(&item.item_id), item.quantity)
+            } else {
+                format!("{}) {}", letter, item_name(&item.item_id))
+            };
+            stdout.queue(MoveTo(2, 2 + i as u16))?;
+            if matches!(self.selection, Selection::Item(index) if index == i) {
+                stdout.queue(PrintStyledContent(style(text).with(Color::Red)))?;
+            } else {
+                stdout.queue(Print(text))?;
+            }
+        }
+
+        stdout.queue(MoveTo(equipment_x, 0))?;
+        stdout.queue(PrintStyledContent(style("Equipment").with(Color::Yellow)))?;
+        if let Some(equipment) = player.equipment() {
+            for (i, (slot, item_id)) in equipment.iter_slots().into_iter().enumerate() {
+                let letter = (b'A' + i as u8) as char;
+                let item = item_id.as_deref().map(item_name).unwrap_or_else(|| "-".to_string());
+                let text = format!("{}) {:<8} {}", letter, slot, item);
+                stdout.queue(MoveTo(equipment_x + 2, 2 + i as u16))?;
+                if matches!(self.selection, Selection::Slot(index) if index == i) {
+                    stdout.queue(PrintStyledContent(style(text).with(Color::Red)))?;
+                } else {
+                    stdout.queue(Print(text))?;
+                }
+            }
+        }
+
+        // details and available commands for the selection
+        let details_y = 4 + items.len().max(EQUIPMENT_SLOTS.len()) as u16;
+        let (item_id, commands) = match self.selection {
+            Selection::None => (None, "a-z select item, A-G select equipment, Esc close".to_string()),
+            Selection::Item(index) => {
+                let item_id = &items[index].item_id;
+                let mut commands = Vec::new();
+                match ASSETS.item_kinds.iter().find(|kind| &kind.id == item_id).map(|kind| &kind.item_type) {
+                    Some(ItemType::Weapon { .. }) | Some(ItemType::Armor { .. }) => commands.push("[e]quip"),
+                    Some(ItemType::Consumable { .. }) => commands.push("[q]uaff"),
+                    _ => {}
+                }
+                commands.push("[d]rop");
+                commands.push("Esc back");
+                (Some(item_id.clone()), commands.join("  "))
+            }
+            Selection::Slot(index) => {
+                let item_id = player.equipment().and_then(|equipment| equipment.iter_slots()[index].1.clone());
+                let commands = if item_id.is_some() { "[r]emove  Esc back" } else { "Esc back" };
+                (item_id, commands.to_string())
+            }
+        };
+
+        if let Some(item_kind) = item_id.and_then(|item_id| ASSETS.item_kinds.iter().find(|kind| kind.id == item_id)) {
+            stdout.queue(MoveTo(0, details_y))?;
+            stdout.queue(PrintStyledContent(style(&item_kind.name).with(Color::White)))?;
+            stdout.queue(MoveTo(0, details_y + 1))?;
+            stdout.queue(PrintStyledContent(style(&item_kind.description).with(Color::Grey)))?;
+        }
+        stdout.queue(MoveTo(0, details_y + 3))?;
+        stdout.queue(PrintStyledContent(style(commands).with(Color::DarkGrey)))?;
+
+        stdout.flush()?;
+        Ok(())
+    }
+}
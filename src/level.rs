@@ -0,0 +1,9 @@
+use crate::{actor_manager::ActorManager, map_manager::MapManager};
+use serde::{Deserialize, Serialize};
+
+/// A floor the player has left, kept whole so it can be restored when they return.
+#[derive(Serialize, Deserialize)]
+pub struct Level {
+    pub map: MapManager,
+    pub actors: ActorManager,
+}
@@ -0,0 +1,80 @@
+use crate::{
+    GameData, GameState,
+    actor::experience_for_level,
+    attributes::{ATTRIBUTE_NAMES, Attributes},
+    playing::Playing,
+};
+use crossterm::{
+    QueueableCommand,
+    cursor::MoveTo,
+    event::KeyCode,
+    style::{Color, Print, PrintStyledContent, Stylize, style},
+    terminal::{Clear, ClearType},
+};
+use std::io::Write;
+
+/// Shown after the player levels up to spend the attribute points they gained.
+pub struct LevelUpMenu {
+    cursor: usize,
+    /// Points assigned on this screen, only applied to the player once all are spent.
+    pending_points: Attributes,
+}
+
+impl LevelUpMenu {
+    pub fn new() -> Self {
+        Self { cursor: 0, pending_points: Attributes::default() }
+    }
+
+    pub fn update(mut self, game_data: &mut GameData) -> GameState {
+        let remaining_points = self.remaining_points(game_data);
+        match game_data.input.last_key() {
+            KeyCode::Char('w') | KeyCode::Up => self.cursor = if self.cursor > 0 { self.cursor - 1 } else { ATTRIBUTE_NAMES.len() - 1 },
+            KeyCode::Char('s') | KeyCode::Down => self.cursor = if self.cursor < ATTRIBUTE_NAMES.len() - 1 { self.cursor + 1 } else { 0 },
+            KeyCode::Char('d') | KeyCode::Right | KeyCode::Char('+') if remaining_points > 0 => *self.pending_points.get_mut(self.cursor) += 1,
+            KeyCode::Char('a') | KeyCode::Left | KeyCode::Char('-') if self.pending_points.get(self.cursor) > 0 => *self.pending_points.get_mut(self.cursor) -= 1,
+            KeyCode::Enter if remaining_points == 0 => {
+                let player = game_data.actors.get_player_actor_mut();
+                if let Err(err) = player.spend_attribute_points(self.pending_points) {
+                    game_data.log(format!("You cannot spend those points: {}.", err.to_lowercase()));
+                }
+                return GameState::Playing(Playing::new());
+            }
+            _ => {}
+        }
+
+        GameState::LevelUpMenu(self)
+    }
+
+    fn remaining_points(&self, game_data: &GameData) -> i32 {
+        game_data.actors.get_player_actor().unspent_points() - self.pending_points.total()
+    }
+
+    pub fn draw(&self, game_data: &GameData) -> std::io::Result<()> {
+        let mut stdout = std::io::stdout();
+        stdout.queue(Clear(ClearType::All))?;
+
+        let player = game_data.actors.get_player_actor();
+        stdout.queue(MoveTo(2, 1))?;
+        stdout.queue(PrintStyledContent(style(format!("{} has reached level {}!", player.name(), player.level())).with(Color::Yellow)))?;
+        stdout.queue(MoveTo(4, 3))?;
+        stdout.queue(Print(format!("Experience {}, next level at {}", player.experience(), experience_for_level(player.level() + 1))))?;
+        stdout.queue(MoveTo(4, 4))?;
+        stdout.queue(Print(format!("Spend your attribute points ({} remaining):", self.remaining_points(game_data))))?;
+
+        let attributes = player.attributes() + self.pending_points;
+        for (i, attribute_name) in ATTRIBUTE_NAMES.iter().enumerate() {
+            let color = if i == self.cursor { Color::Red } else { Color::White };
+            let pending = self.pending_points.get(i);
+            let pending = if pending > 0 { format!("(+{})", pending) } else { String::new() };
+            stdout.queue(MoveTo(4, 6 + i as u16))?;
+            stdout.queue(PrintStyledContent(style(format!("{:<14} {:>3} {}", attribute_name, attributes.get(i), pending)).with(color)))?;
+        }
+
+        let (_, height) = crossterm::terminal::size()?;
+        stdout.queue(MoveTo(2, height.saturating_sub(2)))?;
+        stdout.queue(Print("up/down to choose, left/right to spend points, Enter once all are spent"))?;
+
+        stdout.flush()?;
+        Ok(())
+    }
+}
@@ -3,51 +3,184 @@
 mod actor;
 mod actor_manager;
 mod assets;
+mod attributes;
 mod character_creation_menu;
+mod config;
 mod consts;
 mod debug;
+mod dice;
+mod faction;
+mod game_over;
+mod hud;
 mod input;
 mod inventory;
+mod inventory_menu;
+mod level;
+mod level_up_menu;
 mod main_menu;
+mod map_generator;
 mod map_manager;
+mod message_history;
+mod message_log;
+mod morgue;
+mod noise;
 mod pathfinding;
 mod playing;
 mod position;
+mod save;
 mod shadowcast;
+mod spawner;
+mod spell_menu;
+mod status_effect;
+mod targeting;
 mod terminalguard;
 
+use rand::{Rng, SeedableRng, seq::IndexedRandom};
+use rand_chacha::ChaCha12Rng;
+use std::collections::HashMap;
+
+#[derive(Default)]
 pub enum GameState {
     MainMenu(main_menu::MainMenu),
     CharacterCreationMenu(character_creation_menu::CharacterCreationMenu),
     Playing(playing::Playing),
+    MessageHistory(message_history::MessageHistory),
+    InventoryMenu(inventory_menu::InventoryMenu),
+    LevelUpMenu(level_up_menu::LevelUpMenu),
+    SpellMenu(spell_menu::SpellMenu),
+    Targeting(targeting::Targeting),
+    GameOver(game_over::GameOver),
+    #[default]
     Quit,
 }
 
-impl Default for GameState {
-    fn default() -> Self {
-        GameState::Quit
-    }
-}
-
 pub struct GameData {
+    pub config: config::Config,
     pub input: input::InputState,
+    pub seed: u64,
+    pub rng: ChaCha12Rng,
     pub actors: actor_manager::ActorManager,
     pub map: map_manager::MapManager,
+    pub depth: usize,
+    pub levels: HashMap<usize, level::Level>,
+    pub factions: faction::FactionRelations,
+    pub turns: u64,
+    pub kills: u32,
+    /// Set once the player has died, describing what killed them.
+    pub cause_of_death: Option<String>,
+    pub messages: message_log::MessageLog,
 }
 
 impl GameData {
-    pub fn new() -> Self {
+    pub fn new(config: config::Config) -> Self {
         GameData {
+            config,
             input: input::InputState::new(),
+            seed: 0,
+            rng: ChaCha12Rng::seed_from_u64(0),
             actors: actor_manager::ActorManager::new(),
             map: map_manager::MapManager::new(),
+            depth: 1,
+            levels: HashMap::new(),
+            factions: faction::FactionRelations::new(),
+            turns: 0,
+            kills: 0,
+            cause_of_death: None,
+            messages: message_log::MessageLog::new(),
         }
     }
+
+    /// Reseeds the game RNG at the start of a run, using the seed from the command line if one was given.
+    pub fn reseed(&mut self) {
+        self.seed = self.config.seed.unwrap_or_else(|| rand::rng().random());
+        self.rng = ChaCha12Rng::seed_from_u64(self.seed);
+    }
+
+    /// Starts a fresh run on the first floor with the given player character.
+    pub fn new_game(&mut self, mut player: actor::Actor) {
+        self.reseed();
+        self.actors = actor_manager::ActorManager::new();
+        self.map = map_manager::MapManager::new();
+        self.depth = 1;
+        self.levels.clear();
+        self.factions = faction::FactionRelations::new();
+        self.turns = 0;
+        self.kills = 0;
+        self.cause_of_death = None;
+        self.messages = message_log::MessageLog::new();
+        self.map.build_floor(self.depth, self.config.generator_for_depth(self.depth), &mut self.rng);
+
+        let player_position = *self.map.get_unoccupied_floor_tiles().choose(&mut self.rng).unwrap();
+        spawner::spawn_monsters(&mut self.actors, &mut self.map, self.depth, player_position, &mut self.rng);
+        player.set_position(player_position);
+        self.actors.add_player_actor(player);
+        self.map.set_actor(player_position, 0);
+        self.actors.next_turn(); // start the game on the players turn
+
+        self.update_visibility();
+    }
+
+    /// Moves the player to another depth, storing the current level and restoring or generating the destination.
+    pub fn change_level(&mut self, depth: usize) {
+        let mut player = self.actors.take_player_actor();
+        self.map.remove_actor(player.position());
+
+        let previous_level = level::Level {
+            map: std::mem::replace(&mut self.map, map_manager::MapManager::new()),
+            actors: std::mem::replace(&mut self.actors, actor_manager::ActorManager::new()),
+        };
+        self.levels.insert(self.depth, previous_level);
+
+        let arrival_tile = if depth > self.depth { map_manager::TileType::StairsUp } else { map_manager::TileType::StairsDown };
+        self.depth = depth;
+        let is_new_level = match self.levels.remove(&depth) {
+            Some(level) => {
+                self.map = level.map;
+                self.actors = level.actors;
+                false
+            }
+            None => {
+                self.map.build_floor(depth, self.config.generator_for_depth(depth), &mut self.rng);
+                true
+            }
+        };
+
+        let stairs_position = self.map.find_tile(arrival_tile).expect("every floor has stairs down and every floor below the first has stairs up");
+        if is_new_level {
+            spawner::spawn_monsters(&mut self.actors, &mut self.map, depth, stairs_position, &mut self.rng);
+        }
+
+        // arrive on the stairs leading back, or as close to them as there is room
+        let arrival_position = self.map.nearest_free_tile(stairs_position).expect("a level always has more floor than actors");
+        player.set_position(arrival_position);
+        self.map.set_actor(arrival_position, 0);
+        self.actors.add_player_actor(player);
+        self.update_visibility();
+    }
+
+    /// Updates which tiles are visible from where the player stands.
+    pub fn update_visibility(&mut self) {
+        let player = self.actors.get_player_actor();
+        self.map.update_visibility(player.position(), player.sight_radius());
+    }
+
+    /// Adds a message to the log, stamped with the current turn.
+    pub fn log(&mut self, text: String) {
+        self.messages.add(text, self.turns);
+    }
 }
 
 fn main() -> std::io::Result<()> {
+    let config = match config::Config::from_args() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        }
+    };
+
     let _term = terminalguard::TerminalGuard::new()?;
-    let mut game_data = GameData::new();
+    let mut game_data = GameData::new(config);
     let mut game_state = Some(GameState::MainMenu(main_menu::MainMenu::new()));
 
     loop {
@@ -56,6 +189,12 @@ fn main() -> std::io::Result<()> {
             GameState::MainMenu(main_menu) => main_menu.draw()?,
             GameState::CharacterCreationMenu(character_creation_menu) => character_creation_menu.draw()?,
             GameState::Playing(playing) => playing.draw(&game_data)?,
+            GameState::MessageHistory(message_history) => message_history.draw(&game_data)?,
+            GameState::InventoryMenu(inventory_menu) => inventory_menu.draw(&game_data)?,
+            GameState::LevelUpMenu(level_up_menu) => level_up_menu.draw(&game_data)?,
+            GameState::SpellMenu(spell_menu) => spell_menu.draw(&game_data)?,
+            GameState::Targeting(targeting) => targeting.draw(&game_data)?,
+            GameState::GameOver(game_over) => game_over.draw()?,
             GameState::Quit => break,
         }
 
@@ -67,6 +206,12 @@ fn main() -> std::io::Result<()> {
             GameState::MainMenu(main_menu) => main_menu.update(&mut game_data),
             GameState::CharacterCreationMenu(character_creation_menu) => character_creation_menu.update(&mut game_data),
             GameState::Playing(playing) => playing.update(&mut game_data),
+            GameState::MessageHistory(message_history) => message_history.update(&mut game_data),
+            GameState::InventoryMenu(inventory_menu) => inventory_menu.update(&mut game_data),
+            GameState::LevelUpMenu(level_up_menu) => level_up_menu.update(&mut game_data),
+            GameState::SpellMenu(spell_menu) => spell_menu.update(&mut game_data),
+            GameState::Targeting(targeting) => targeting.update(&mut game_data),
+            GameState::GameOver(game_over) => game_over.update(&mut game_data),
             GameState::Quit => GameState::Quit,
         });
     }
@@ -1,11 +1,4 @@
-use crate::{
-    GameData, GameState,
-    actor::{Actor, CharacterStats},
-    actor_manager::ActorManager,
-    character_creation_menu::CharacterCreationMenu,
-    map_manager::MapManager,
-    position::Position,
-};
+use crate::{GameData, GameState, character_creation_menu::CharacterCreationMenu, playing::Playing, save};
 use crossterm::{
     QueueableCommand,
     cursor::MoveTo,
@@ -25,14 +18,19 @@ const TITLE: [&str; 4] = [
     "▐▙▄▄▀▐▙▄▄▖▐▌  ▐▌▝▚▄▞▘▐▌  ▐▌    ▐▌ ▐▌▗▄█▄▖▐▌  ▐▌▝▚▄▞▘",
 ];
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct MainMenu {
     cursor: u8,
+    message: Option<String>,
 }
 
 impl MainMenu {
     pub fn new() -> Self {
-        Self { cursor: 0 }
+        Self { cursor: 0, message: None }
+    }
+
+    pub fn with_message(message: String) -> Self {
+        Self { cursor: 0, message: Some(message) }
     }
 
     pub fn update(mut self, game_data: &mut GameData) -> GameState {
@@ -44,24 +42,11 @@ impl MainMenu {
                 self.cursor = if (self.cursor as usize) < MENU_ITEMS.len() - 1 { self.cursor + 1 } else { 0 };
             }
             KeyCode::Enter => match self.cursor {
-                0 => {
-                    // setup new game
-                    game_data.actors = ActorManager::new();
-                    game_data.map = MapManager::new();
-                    game_data.map.build_floor();
-
-                    let player_position = Position { x: 10, y: 10 };
-                    let player_character_stats = CharacterStats::new("Hero".to_string(), "player".to_string());
-
-                    let player_actor = Actor::new("human".to_string(), Some(player_character_stats), player_position);
-                    let actor_id = game_data.actors.add_actor(player_actor);
-                    game_data.map.set_actor(player_position, actor_id);
-
-                    game_data.map.update_visibility(player_position);
-
-                    return GameState::CharacterCreationMenu(CharacterCreationMenu::new());
-                }
-                1 => {} // TODO load game not yet implemented
+                0 => return GameState::CharacterCreationMenu(CharacterCreationMenu::new()),
+                1 => match save::load_game(game_data) {
+                    Ok(()) => return GameState::Playing(Playing::new()),
+                    Err(err) => self.message = Some(err),
+                },
                 2 => return GameState::Quit,
                 _ => {}
             },
@@ -77,7 +62,6 @@ impl MainMenu {
         let (cols, _) = crossterm::terminal::size()?;
         stdout.queue(Clear(ClearType::All))?;
 
-        
         let prefix_x = center_x(TITLE_PREFIX, cols);
         stdout.queue(MoveTo(prefix_x, 4))?;
         stdout.queue(PrintStyledContent(style(TITLE_PREFIX).with(Color::Magenta).attribute(Attribute::Bold)))?;
@@ -92,10 +76,15 @@ impl MainMenu {
             if i as u8 == self.cursor {
                 stdout.queue(PrintStyledContent(style(item).with(Color::Red)))?;
             } else {
-                stdout.queue(Print(format!("{}", item)))?;
+                stdout.queue(Print(item.to_string()))?;
             }
         }
 
+        if let Some(message) = &self.message {
+            stdout.queue(MoveTo(20, 13 + MENU_ITEMS.len() as u16))?;
+            stdout.queue(PrintStyledContent(style(message).with(Color::Yellow)))?;
+        }
+
         stdout.flush()?;
         Ok(())
     }
@@ -0,0 +1,86 @@
+use super::{MapGenerator, Room, carve_l_tunnel, carve_room, place_doors, remove_unreachable, solid_layout};
+use crate::{consts::DUNGEON_SIZE, map_manager::TileType};
+use rand::{Rng, RngCore, seq::IndexedRandom};
+
+/// Recursively splits the map into partitions, places a room in each leaf and joins sibling partitions with tunnels.
+pub struct Bsp {
+    pub min_leaf_size: usize,
+    pub max_splits: usize,
+    pub min_room_size: usize,
+}
+
+impl Bsp {
+    pub fn new(depth: usize) -> Self {
+        Self {
+            min_leaf_size: 10usize.saturating_sub(depth / 3).max(8),
+            max_splits: (5 + depth / 3).min(7),
+            min_room_size: 4,
+        }
+    }
+
+    /// Returns every room placed within the partition so the caller can join it to its sibling.
+    fn split(&self, area: Room, depth: usize, layout: &mut [TileType], rooms: &mut Vec<Room>, rng: &mut dyn RngCore) -> Vec<Room> {
+        let can_split_vertically = area.width >= self.min_leaf_size * 2;
+        let can_split_horizontally = area.height >= self.min_leaf_size * 2;
+
+        if depth >= self.max_splits || (!can_split_vertically && !can_split_horizontally) {
+            let room = self.place_room(area, rng);
+            carve_room(layout, &room);
+            rooms.push(room);
+            return vec![room];
+        }
+
+        // prefer cutting across the longer side so partitions stay roughly square
+        let vertical = match (can_split_vertically, can_split_horizontally) {
+            (true, false) => true,
+            (false, true) => false,
+            _ if area.width as f32 > area.height as f32 * 1.25 => true,
+            _ if area.height as f32 > area.width as f32 * 1.25 => false,
+            _ => rng.random_bool(0.5),
+        };
+
+        let (first, second) = if vertical {
+            let cut = rng.random_range(self.min_leaf_size..=area.width - self.min_leaf_size);
+            (Room::new(area.x, area.y, cut, area.height), Room::new(area.x + cut, area.y, area.width - cut, area.height))
+        } else {
+            let cut = rng.random_range(self.min_leaf_size..=area.height - self.min_leaf_size);
+            (Room::new(area.x, area.y, area.width, cut), Room::new(area.x, area.y + cut, area.width, area.height - cut))
+        };
+
+        let mut first_rooms = self.split(first, depth + 1, layout, rooms, rng);
+        let second_rooms = self.split(second, depth + 1, layout, rooms, rng);
+
+        let from = *first_rooms.choose(rng).unwrap();
+        let to = *second_rooms.choose(rng).unwrap();
+        carve_l_tunnel(layout, from.center(), to.center(), rng);
+
+        first_rooms.extend(second_rooms);
+        first_rooms
+    }
+
+    /// Places a randomly sized room inside the leaf, leaving at least one wall tile on each side.
+    fn place_room(&self, leaf: Room, rng: &mut dyn RngCore) -> Room {
+        let max_width = (leaf.width - 2).max(self.min_room_size);
+        let max_height = (leaf.height - 2).max(self.min_room_size);
+        let width = rng.random_range(self.min_room_size..=max_width);
+        let height = rng.random_range(self.min_room_size..=max_height);
+        let x = leaf.x + 1 + rng.random_range(0..=max_width - width);
+        let y = leaf.y + 1 + rng.random_range(0..=max_height - height);
+        Room::new(x, y, width, height)
+    }
+}
+
+impl MapGenerator for Bsp {
+    fn generate(&self, rng: &mut dyn RngCore) -> Vec<TileType> {
+        let mut layout = solid_layout();
+        let mut rooms = Vec::new();
+
+        let root = Room::new(1, 1, DUNGEON_SIZE - 2, DUNGEON_SIZE - 2);
+        self.split(root, 0, &mut layout, &mut rooms, rng);
+
+        place_doors(&mut layout, &rooms, rng);
+        remove_unreachable(&mut layout, rooms[0].center());
+
+        layout
+    }
+}
@@ -0,0 +1,84 @@
+use super::{MapGenerator, flood_fill, index, remove_unreachable, solid_layout};
+use crate::{consts::DUNGEON_SIZE, map_manager::TileType};
+use rand::{Rng, RngCore};
+
+/// Grows organic caves by randomly filling the map and repeatedly smoothing it, keeping only the largest cave.
+pub struct Cellular {
+    pub initial_wall_chance: f64,
+    pub iterations: usize,
+}
+
+impl Cellular {
+    pub fn new(depth: usize) -> Self {
+        Self {
+            initial_wall_chance: (0.38 + depth as f64 * 0.002).min(0.41),
+            iterations: 5,
+        }
+    }
+}
+
+impl MapGenerator for Cellular {
+    fn generate(&self, rng: &mut dyn RngCore) -> Vec<TileType> {
+        let mut layout = solid_layout();
+        for y in 1..DUNGEON_SIZE - 1 {
+            for x in 1..DUNGEON_SIZE - 1 {
+                if !rng.random_bool(self.initial_wall_chance) {
+                    layout[index(x, y)] = TileType::Floor;
+                }
+            }
+        }
+
+        for _ in 0..self.iterations {
+            let previous = layout.clone();
+            for y in 1..DUNGEON_SIZE - 1 {
+                for x in 1..DUNGEON_SIZE - 1 {
+                    let walls = count_adjacent_walls(&previous, x, y);
+                    layout[index(x, y)] = if walls >= 5 {
+                        TileType::Wall
+                    } else if walls <= 2 {
+                        TileType::Floor
+                    } else {
+                        previous[index(x, y)]
+                    };
+                }
+            }
+        }
+
+        // keep the largest cave so the level is a single connected region
+        let mut visited = vec![false; layout.len()];
+        let mut largest: Option<((usize, usize), usize)> = None;
+        for y in 1..DUNGEON_SIZE - 1 {
+            for x in 1..DUNGEON_SIZE - 1 {
+                if layout[index(x, y)] == TileType::Wall || visited[index(x, y)] {
+                    continue;
+                }
+                let region = flood_fill(&layout, (x, y));
+                let size = region.iter().filter(|reached| **reached).count();
+                for (i, reached) in region.into_iter().enumerate() {
+                    visited[i] |= reached;
+                }
+                if largest.is_none_or(|(_, largest_size)| size > largest_size) {
+                    largest = Some(((x, y), size));
+                }
+            }
+        }
+
+        if let Some((start, _)) = largest {
+            remove_unreachable(&mut layout, start);
+        }
+
+        layout
+    }
+}
+
+fn count_adjacent_walls(layout: &[TileType], x: usize, y: usize) -> usize {
+    let mut walls = 0;
+    for ny in y - 1..=y + 1 {
+        for nx in x - 1..=x + 1 {
+            if (nx, ny) != (x, y) && layout[index(nx, ny)] == TileType::Wall {
+                walls += 1;
+            }
+        }
+    }
+    walls
+}
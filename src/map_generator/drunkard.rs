@@ -0,0 +1,56 @@
+use super::{MapGenerator, index, solid_layout};
+use crate::{consts::DUNGEON_SIZE, map_manager::TileType};
+use rand::{Rng, RngCore, seq::IndexedRandom};
+
+const MAX_WALKERS: usize = 500;
+
+/// Sends walkers stumbling randomly from already dug floor until enough of the map has been carved out.
+pub struct Drunkard {
+    pub target_floor_ratio: f32,
+    pub walker_lifetime: usize,
+}
+
+impl Drunkard {
+    pub fn new(depth: usize) -> Self {
+        Self {
+            target_floor_ratio: (0.4 - depth as f32 * 0.01).max(0.25),
+            walker_lifetime: 400,
+        }
+    }
+}
+
+impl MapGenerator for Drunkard {
+    fn generate(&self, rng: &mut dyn RngCore) -> Vec<TileType> {
+        let mut layout = solid_layout();
+        let target_floor_count = ((DUNGEON_SIZE - 2) * (DUNGEON_SIZE - 2)) as f32 * self.target_floor_ratio;
+
+        let center = (DUNGEON_SIZE / 2, DUNGEON_SIZE / 2);
+        layout[index(center.0, center.1)] = TileType::Floor;
+        let mut floor_tiles = vec![center];
+
+        // every walker starts on existing floor, so the dug area is always connected
+        for _ in 0..MAX_WALKERS {
+            if floor_tiles.len() as f32 >= target_floor_count {
+                break;
+            }
+
+            let (mut x, mut y) = *floor_tiles.choose(rng).unwrap();
+            for _ in 0..self.walker_lifetime {
+                match rng.random_range(0..4) {
+                    0 if x > 1 => x -= 1,
+                    1 if x < DUNGEON_SIZE - 2 => x += 1,
+                    2 if y > 1 => y -= 1,
+                    3 if y < DUNGEON_SIZE - 2 => y += 1,
+                    _ => {}
+                }
+
+                if layout[index(x, y)] == TileType::Wall {
+                    layout[index(x, y)] = TileType::Floor;
+                    floor_tiles.push((x, y));
+                }
+            }
+        }
+
+        layout
+    }
+}
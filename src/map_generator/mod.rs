@@ -0,0 +1,262 @@
+pub mod bsp;
+pub mod cellular;
+pub mod drunkard;
+pub mod tunneller;
+
+use crate::{consts::DUNGEON_SIZE, map_manager::TileType};
+use rand::{Rng, RngCore};
+use std::collections::VecDeque;
+
+/// Layouts with less of the map open than this are rejected as degenerate.
+pub const MIN_FLOOR_RATIO: f32 = 0.15;
+
+/// Produces a flat `DUNGEON_SIZE * DUNGEON_SIZE` tile layout, indexed `y * DUNGEON_SIZE + x`, for `MapManager` to build a floor from.
+pub trait MapGenerator {
+    fn generate(&self, rng: &mut dyn RngCore) -> Vec<TileType>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeneratorKind {
+    Tunneller,
+    Bsp,
+    Cellular,
+    Drunkard,
+}
+
+impl GeneratorKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "tunneller" => Some(GeneratorKind::Tunneller),
+            "bsp" => Some(GeneratorKind::Bsp),
+            "cellular" => Some(GeneratorKind::Cellular),
+            "drunkard" => Some(GeneratorKind::Drunkard),
+            _ => None,
+        }
+    }
+
+    /// Cycles through the layouts as the player descends so consecutive floors feel different.
+    pub fn for_depth(depth: usize) -> Self {
+        const ORDER: [GeneratorKind; 4] = [GeneratorKind::Tunneller, GeneratorKind::Bsp, GeneratorKind::Drunkard, GeneratorKind::Cellular];
+        ORDER[depth.saturating_sub(1) % ORDER.len()]
+    }
+
+    /// Builds the generator with parameters tuned for the given depth, deeper floors being more cramped.
+    pub fn generator(&self, depth: usize) -> Box<dyn MapGenerator> {
+        match self {
+            GeneratorKind::Tunneller => Box::new(tunneller::Tunneller::new(depth)),
+            GeneratorKind::Bsp => Box::new(bsp::Bsp::new(depth)),
+            GeneratorKind::Cellular => Box::new(cellular::Cellular::new(depth)),
+            GeneratorKind::Drunkard => Box::new(drunkard::Drunkard::new(depth)),
+        }
+    }
+}
+
+/// Returns true if every passable tile in the layout can be reached from every other.
+pub fn is_connected(layout: &[TileType]) -> bool {
+    let Some(start) = layout.iter().position(|tile_type| *tile_type != TileType::Wall) else {
+        return false;
+    };
+    let reachable = flood_fill(layout, (start % DUNGEON_SIZE, start / DUNGEON_SIZE));
+    layout.iter().zip(reachable).all(|(tile_type, reached)| *tile_type == TileType::Wall || reached)
+}
+
+/// Whether a layout is fit to play on: one connected region with enough room to move around in.
+pub fn is_playable(layout: &[TileType]) -> bool {
+    is_connected(layout) && floor_ratio(layout) >= MIN_FLOOR_RATIO
+}
+
+/// Fraction of the layout that is passable.
+pub fn floor_ratio(layout: &[TileType]) -> f32 {
+    let floor_count = layout.iter().filter(|tile_type| **tile_type != TileType::Wall).count();
+    floor_count as f32 / layout.len() as f32
+}
+
+/// Converts a grid coordinate into an index into a layout.
+pub fn index(x: usize, y: usize) -> usize {
+    y * DUNGEON_SIZE + x
+}
+
+#[derive(Clone, Copy)]
+pub struct Room {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Room {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self { x, y, width, height }
+    }
+
+    pub fn center(&self) -> (usize, usize) {
+        (self.x + self.width / 2, self.y + self.height / 2)
+    }
+
+    /// Returns true if the rooms overlap or are closer than `margin` tiles to each other.
+    pub fn intersects(&self, other: &Room, margin: usize) -> bool {
+        self.x < other.x + other.width + margin && other.x < self.x + self.width + margin && self.y < other.y + other.height + margin && other.y < self.y + self.height + margin
+    }
+}
+
+/// Creates a layout of solid wall for generators to carve into.
+pub fn solid_layout() -> Vec<TileType> {
+    vec![TileType::Wall; DUNGEON_SIZE * DUNGEON_SIZE]
+}
+
+pub fn carve_room(layout: &mut [TileType], room: &Room) {
+    for y in room.y..room.y + room.height {
+        for x in room.x..room.x + room.width {
+            layout[index(x, y)] = TileType::Floor;
+        }
+    }
+}
+
+pub fn carve_horizontal_tunnel(layout: &mut [TileType], x1: usize, x2: usize, y: usize) {
+    for x in x1.min(x2)..=x1.max(x2) {
+        layout[index(x, y)] = TileType::Floor;
+    }
+}
+
+pub fn carve_vertical_tunnel(layout: &mut [TileType], y1: usize, y2: usize, x: usize) {
+    for y in y1.min(y2)..=y1.max(y2) {
+        layout[index(x, y)] = TileType::Floor;
+    }
+}
+
+/// Joins two points with an L shaped tunnel, randomly choosing which leg is dug first.
+pub fn carve_l_tunnel(layout: &mut [TileType], from: (usize, usize), to: (usize, usize), rng: &mut dyn RngCore) {
+    if rng.random_bool(0.5) {
+        carve_horizontal_tunnel(layout, from.0, to.0, from.1);
+        carve_vertical_tunnel(layout, from.1, to.1, to.0);
+    } else {
+        carve_vertical_tunnel(layout, from.1, to.1, from.0);
+        carve_horizontal_tunnel(layout, from.0, to.0, to.1);
+    }
+}
+
+/// Turns floor tiles on the wall ring around each room into doors, but only where the
+/// opening is a single tile wide so the door sits snugly between two walls.
+pub fn place_doors(layout: &mut [TileType], rooms: &[Room], rng: &mut dyn RngCore) {
+    for room in rooms {
+        if room.x == 0 || room.y == 0 || room.x + room.width >= DUNGEON_SIZE || room.y + room.height >= DUNGEON_SIZE {
+            continue;
+        }
+
+        let top = room.y - 1;
+        let bottom = room.y + room.height;
+        let left = room.x - 1;
+        let right = room.x + room.width;
+
+        let mut candidates = Vec::new();
+        for x in room.x..right {
+            for y in [top, bottom] {
+                if layout[index(x, y)] == TileType::Floor && layout[index(x - 1, y)] == TileType::Wall && layout[index(x + 1, y)] == TileType::Wall {
+                    candidates.push((x, y));
+                }
+            }
+        }
+        for y in room.y..bottom {
+            for x in [left, right] {
+                if layout[index(x, y)] == TileType::Floor && layout[index(x, y - 1)] == TileType::Wall && layout[index(x, y + 1)] == TileType::Wall {
+                    candidates.push((x, y));
+                }
+            }
+        }
+
+        for (x, y) in candidates {
+            layout[index(x, y)] = if rng.random_bool(0.7) { TileType::ClosedDoor } else { TileType::OpenDoor };
+        }
+    }
+}
+
+/// Flood fills from `start` and walls off every passable tile that could not be reached,
+/// guaranteeing that the whole floor is one connected region.
+pub fn remove_unreachable(layout: &mut [TileType], start: (usize, usize)) {
+    let reachable = flood_fill(layout, start);
+    for (i, tile_type) in layout.iter_mut().enumerate() {
+        if *tile_type != TileType::Wall && !reachable[i] {
+            *tile_type = TileType::Wall;
+        }
+    }
+}
+
+/// Returns a mask of every tile reachable from `start` without passing through walls.
+pub fn flood_fill(layout: &[TileType], start: (usize, usize)) -> Vec<bool> {
+    let mut reachable = vec![false; layout.len()];
+    if layout[index(start.0, start.1)] == TileType::Wall {
+        return reachable;
+    }
+
+    let mut queue = VecDeque::new();
+    reachable[index(start.0, start.1)] = true;
+    queue.push_back(start);
+
+    while let Some((x, y)) = queue.pop_front() {
+        for (dx, dy) in [(-1i64, -1i64), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+            let nx = x as i64 + dx;
+            let ny = y as i64 + dy;
+            if nx < 0 || ny < 0 || nx >= DUNGEON_SIZE as i64 || ny >= DUNGEON_SIZE as i64 {
+                continue;
+            }
+            let neighbour = index(nx as usize, ny as usize);
+            if !reachable[neighbour] && layout[neighbour] != TileType::Wall {
+                reachable[neighbour] = true;
+                queue.push_back((nx as usize, ny as usize));
+            }
+        }
+    }
+
+    reachable
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+
+    const KINDS: [GeneratorKind; 4] = [GeneratorKind::Tunneller, GeneratorKind::Bsp, GeneratorKind::Cellular, GeneratorKind::Drunkard];
+
+    fn assert_playable(kind: GeneratorKind, depth: usize) {
+        let generator = kind.generator(depth);
+        for seed in 0..20 {
+            let layout = generator.generate(&mut ChaCha12Rng::seed_from_u64(seed));
+            assert_eq!(layout.len(), DUNGEON_SIZE * DUNGEON_SIZE);
+            assert!(is_connected(&layout), "{:?} at depth {} with seed {} is not connected", kind, depth, seed);
+            let ratio = floor_ratio(&layout);
+            assert!(ratio >= MIN_FLOOR_RATIO, "{:?} at depth {} with seed {} has a floor ratio of {}", kind, depth, seed, ratio);
+        }
+    }
+
+    #[test]
+    fn generators_are_playable_at_every_depth() {
+        for kind in KINDS {
+            for depth in [1, 5, 12, 30] {
+                assert_playable(kind, depth);
+            }
+        }
+    }
+
+    #[test]
+    fn split_layout_is_not_connected() {
+        let mut layout = solid_layout();
+        carve_room(&mut layout, &Room::new(2, 2, 4, 4));
+        assert!(is_connected(&layout));
+        carve_room(&mut layout, &Room::new(20, 20, 4, 4));
+        assert!(!is_connected(&layout));
+        carve_horizontal_tunnel(&mut layout, 4, 21, 4);
+        carve_vertical_tunnel(&mut layout, 4, 21, 21);
+        assert!(is_connected(&layout));
+    }
+
+    #[test]
+    fn floor_ratio_counts_doors_as_open() {
+        let mut layout = solid_layout();
+        assert_eq!(floor_ratio(&layout), 0.0);
+        layout[index(1, 1)] = TileType::Floor;
+        layout[index(2, 1)] = TileType::ClosedDoor;
+        assert_eq!(floor_ratio(&layout), 2.0 / (DUNGEON_SIZE * DUNGEON_SIZE) as f32);
+        assert!(!is_playable(&layout));
+    }
+}
@@ -0,0 +1,57 @@
+use super::{MapGenerator, Room, carve_l_tunnel, carve_room, place_doors, remove_unreachable, solid_layout};
+use crate::{consts::DUNGEON_SIZE, map_manager::TileType};
+use rand::{Rng, RngCore};
+
+const PLACEMENT_ATTEMPTS: usize = 300;
+
+/// Scatters non-overlapping rooms across a solid grid and tunnels between them in the order they were placed.
+pub struct Tunneller {
+    pub max_rooms: usize,
+    pub min_room_size: usize,
+    pub max_room_size: usize,
+}
+
+impl Tunneller {
+    pub fn new(depth: usize) -> Self {
+        Self {
+            max_rooms: 30,
+            min_room_size: 4,
+            max_room_size: 12usize.saturating_sub(depth / 2).max(6),
+        }
+    }
+}
+
+impl MapGenerator for Tunneller {
+    fn generate(&self, rng: &mut dyn RngCore) -> Vec<TileType> {
+        let mut layout = solid_layout();
+        let mut rooms: Vec<Room> = Vec::new();
+
+        for _ in 0..PLACEMENT_ATTEMPTS {
+            if rooms.len() >= self.max_rooms {
+                break;
+            }
+
+            let width = rng.random_range(self.min_room_size..=self.max_room_size);
+            let height = rng.random_range(self.min_room_size..=self.max_room_size);
+            // keep a one tile wall border around the edge of the map
+            let x = rng.random_range(1..DUNGEON_SIZE - width - 1);
+            let y = rng.random_range(1..DUNGEON_SIZE - height - 1);
+            let room = Room::new(x, y, width, height);
+
+            if rooms.iter().any(|other| room.intersects(other, 2)) {
+                continue;
+            }
+
+            carve_room(&mut layout, &room);
+            if let Some(previous) = rooms.last() {
+                carve_l_tunnel(&mut layout, previous.center(), room.center(), rng);
+            }
+            rooms.push(room);
+        }
+
+        place_doors(&mut layout, &rooms, rng);
+        remove_unreachable(&mut layout, rooms[0].center());
+
+        layout
+    }
+}
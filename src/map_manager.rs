@@ -1,7 +1,27 @@
 use crossterm::style::Color;
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
 
-use crate::{consts::DUNGEON_SIZE, position::Position};
+use crate::{
+    assets::ASSETS,
+    consts::DUNGEON_SIZE,
+    inventory::Inventory,
+    map_generator::{GeneratorKind, is_playable},
+    position::Position,
+};
 
+/// Layouts a generator may have rejected before giving up on it for the tunneller.
+const MAX_GENERATION_ATTEMPTS: usize = 20;
+/// Braziers lighting the first floor, deeper floors getting one fewer every few levels.
+const BRAZIERS_ON_FIRST_FLOOR: usize = 8;
+const FLOORS_PER_BRAZIER_LOST: usize = 3;
+const MIN_BRAZIERS: usize = 2;
+/// How far a brazier's light reaches, stopped by walls and closed doors.
+const LIGHT_RADIUS: i64 = 6;
+
+#[derive(Serialize, Deserialize)]
 pub struct MapManager {
     tiles: Vec<Tile>,
 }
@@ -11,26 +31,58 @@ impl MapManager {
         Self { tiles: Vec::with_capacity(DUNGEON_SIZE * DUNGEON_SIZE) }
     }
 
-    pub fn build_floor(&mut self) {
+    pub fn build_floor(&mut self, depth: usize, generator_kind: GeneratorKind, rng: &mut ChaCha12Rng) {
         self.tiles.clear();
-        // let layout = tunneller::generate_tunneller_layout();
-        // for y in 0..DUNGEON_SIZE {
-        //     for x in 0..DUNGEON_SIZE {
-        //         self.tiles.push(Tile::new(layout[y * DUNGEON_SIZE + x]));
-        //     }
-        // }
+        let layout = Self::generate_layout(depth, generator_kind, rng);
+        for y in 0..DUNGEON_SIZE {
+            for x in 0..DUNGEON_SIZE {
+                self.tiles.push(Tile::new(layout[y * DUNGEON_SIZE + x]));
+            }
+        }
 
-        for _ in 0..(DUNGEON_SIZE * DUNGEON_SIZE) {
-            self.tiles.push(Tile::new(TileType::Floor));
+        // the first floor has no way back up
+        let mut floor_positions = self.get_unoccupied_floor_tiles();
+        floor_positions.shuffle(rng);
+        let down_position = floor_positions.pop().unwrap();
+        self.get_tile_mut(down_position).unwrap().tile_type = TileType::StairsDown;
+        if depth > 1 {
+            let up_position = floor_positions.pop().unwrap();
+            self.get_tile_mut(up_position).unwrap().tile_type = TileType::StairsUp;
         }
 
-        self.tiles[5 * DUNGEON_SIZE + 5] = Tile::new(TileType::Wall);
-        self.tiles[5 * DUNGEON_SIZE + 6] = Tile::new(TileType::Wall);
-        self.tiles[5 * DUNGEON_SIZE + 7] = Tile::new(TileType::ClosedDoor);
-        self.tiles[5 * DUNGEON_SIZE + 8] = Tile::new(TileType::Wall);
-        self.tiles[5 * DUNGEON_SIZE + 9] = Tile::new(TileType::OpenDoor);
-        self.tiles[5 * DUNGEON_SIZE + 10] = Tile::new(TileType::Wall);
-        self.tiles[5 * DUNGEON_SIZE + 11] = Tile::new(TileType::Wall);
+        self.place_braziers(depth, rng);
+    }
+
+    /// Rejects the rare degenerate layout, such as a cave that collapsed into a tiny pocket. A generator that keeps failing
+    /// is given up on for the tunneller, which always joins up its rooms.
+    fn generate_layout(depth: usize, generator_kind: GeneratorKind, rng: &mut ChaCha12Rng) -> Vec<TileType> {
+        let mut layout = Vec::new();
+        for kind in [generator_kind, GeneratorKind::Tunneller] {
+            let generator = kind.generator(depth);
+            for _ in 0..MAX_GENERATION_ATTEMPTS {
+                layout = generator.generate(rng);
+                if is_playable(&layout) {
+                    return layout;
+                }
+            }
+        }
+        layout
+    }
+
+    /// A map of exactly the given layout, for tests that need a particular arrangement of tiles.
+    #[cfg(test)]
+    pub fn from_layout(layout: &[TileType]) -> Self {
+        Self {
+            tiles: layout.iter().map(|tile_type| Tile::new(*tile_type)).collect(),
+        }
+    }
+
+    pub fn find_tile(&self, tile_type: TileType) -> Option<Position> {
+        let index = self.tiles.iter().position(|tile| tile.tile_type == tile_type)?;
+        Some(Position {
+            x: (index % DUNGEON_SIZE) as i64,
+            y: (index / DUNGEON_SIZE) as i64,
+        })
     }
 
     pub fn get_tile(&self, position: Position) -> Option<&Tile> {
@@ -49,6 +101,57 @@ impl MapManager {
         self.tiles.get_mut(index)
     }
 
+    /// Whether a tile is in the light of a brazier, rather than in the dark.
+    pub fn is_lit(&self, position: Position) -> bool {
+        self.get_tile(position).is_some_and(|tile| tile.lit)
+    }
+
+    /// Stands braziers out in the open, fewer of them the deeper the floor, and lights every tile they can see.
+    /// Each one is ringed by floor so it never blocks a corridor or splits the level.
+    fn place_braziers(&mut self, depth: usize, rng: &mut ChaCha12Rng) {
+        let brazier_count = BRAZIERS_ON_FIRST_FLOOR.saturating_sub((depth - 1) / FLOORS_PER_BRAZIER_LOST).max(MIN_BRAZIERS);
+        let mut candidates = self.get_unoccupied_floor_tiles();
+        candidates.shuffle(rng);
+
+        let mut braziers = Vec::new();
+        for position in candidates {
+            if braziers.len() >= brazier_count {
+                break;
+            }
+            let surrounded_by_floor = position.get_neighbours().into_iter().all(|(neighbour, _)| self.get_tile(neighbour).is_some_and(|tile| tile.tile_type == TileType::Floor));
+            if surrounded_by_floor {
+                self.get_tile_mut(position).unwrap().tile_type = TileType::Brazier;
+                braziers.push(position);
+            }
+        }
+
+        for brazier in braziers {
+            for lit_position in self.shadowcast(brazier, LIGHT_RADIUS) {
+                self.tiles[(lit_position.y as usize) * DUNGEON_SIZE + (lit_position.x as usize)].lit = true;
+            }
+        }
+    }
+
+    /// The closest walkable tile to `start` that nobody is standing on, searching outward through anything passable.
+    pub fn nearest_free_tile(&self, start: Position) -> Option<Position> {
+        let mut visited = HashSet::from([start]);
+        let mut frontier = VecDeque::from([start]);
+        while let Some(position) = frontier.pop_front() {
+            let Some(tile) = self.get_tile(position) else {
+                continue;
+            };
+            if tile.is_walkable() && tile.actor_id().is_none() {
+                return Some(position);
+            }
+            for (neighbour, _) in position.get_neighbours() {
+                if self.get_tile(neighbour).is_some_and(|tile| tile.movement_cost() != u32::MAX) && visited.insert(neighbour) {
+                    frontier.push_back(neighbour);
+                }
+            }
+        }
+        None
+    }
+
     /// Useful for placing actors/items randomly
     pub fn get_unoccupied_floor_tiles(&self) -> Vec<Position> {
         let mut floor_positions = Vec::new();
@@ -65,7 +168,31 @@ impl MapManager {
         floor_positions
     }
 
-    pub fn update_visibility(&mut self, position: Position) {
+    /// Ids of every actor standing on a currently visible tile.
+    pub fn visible_actor_ids(&self) -> Vec<usize> {
+        self.tiles.iter().filter(|tile| matches!(tile.visibility, Visibility::Visible)).filter_map(|tile| tile.actor_id).collect()
+    }
+
+    /// The flight of a projectile from `from` towards `to`, stopping before any tile it could not come to rest on, such as
+    /// a wall or brazier, or on the first actor in the way.
+    pub fn trace_projectile(&self, from: Position, to: Position) -> Vec<Position> {
+        let mut path = Vec::new();
+        for position in from.line_to(to) {
+            let Some(tile) = self.get_tile(position) else {
+                break;
+            };
+            if !tile.is_walkable() {
+                break;
+            }
+            path.push(position);
+            if tile.actor_id().is_some() {
+                break;
+            }
+        }
+        path
+    }
+
+    pub fn update_visibility(&mut self, position: Position, sight_radius: i64) {
         // Set all visible tiles to explored
         for tile in &mut self.tiles {
             if let Visibility::Visible = tile.visibility {
@@ -74,7 +201,7 @@ impl MapManager {
         }
 
         // Run shadowcast from the given position
-        for tile_pos in self.shadowcast(position) {
+        for tile_pos in self.shadowcast(position, sight_radius) {
             self.tiles[(tile_pos.y as usize) * DUNGEON_SIZE + (tile_pos.x as usize)].visibility = Visibility::Visible;
         }
     }
@@ -99,11 +226,15 @@ impl MapManager {
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct Tile {
     tile_type: TileType,
     visibility: Visibility,
     actor_id: Option<usize>,
-    // items: Inventory,
+    #[serde(default = "Inventory::new", skip_serializing_if = "Inventory::is_empty")]
+    items: Inventory,
+    /// Lit by a brazier, making anyone standing here easier to spot.
+    lit: bool,
 }
 
 impl Tile {
@@ -112,7 +243,8 @@ impl Tile {
             tile_type,
             visibility: Visibility::Hidden,
             actor_id: None,
-            // items: Inventory::new(),
+            items: Inventory::new(),
+            lit: false,
         }
     }
 
@@ -124,20 +256,46 @@ impl Tile {
         self.actor_id
     }
 
+    /// The pile of items lying on the tile.
+    pub fn items(&self) -> &Inventory {
+        &self.items
+    }
+
+    pub fn items_mut(&mut self) -> &mut Inventory {
+        &mut self.items
+    }
+
+    /// The glyph of the item on top of the pile, if the tile has been seen.
+    pub fn item_glyph(&self) -> Option<(char, Color)> {
+        if let Visibility::Hidden = self.visibility {
+            return None;
+        }
+        let item = self.items.items().last()?;
+        let item_kind = ASSETS.item_kinds.iter().find(|kind| kind.id == item.item_id)?;
+        Some(item_kind.item_type.glyph())
+    }
+
     pub fn glyph(&self) -> (char, Color) {
         match self.visibility {
             Visibility::Hidden => (' ', Color::Black),
             Visibility::Visible => match self.tile_type {
+                TileType::Floor if self.lit => ('.', Color::DarkYellow),
                 TileType::Floor => ('.', Color::Grey),
                 TileType::Wall => ('#', Color::Grey),
                 TileType::ClosedDoor => ('+', Color::Yellow),
                 TileType::OpenDoor => ('-', Color::Yellow),
+                TileType::StairsDown => ('>', Color::White),
+                TileType::StairsUp => ('<', Color::White),
+                TileType::Brazier => ('&', Color::Yellow),
             },
             Visibility::Explored => match self.tile_type {
                 TileType::Floor => ('.', Color::DarkGrey),
                 TileType::Wall => ('#', Color::DarkGrey),
                 TileType::ClosedDoor => ('+', Color::DarkYellow),
                 TileType::OpenDoor => ('-', Color::DarkYellow),
+                TileType::StairsDown => ('>', Color::Grey),
+                TileType::StairsUp => ('<', Color::Grey),
+                TileType::Brazier => ('&', Color::DarkYellow),
             },
         }
     }
@@ -148,15 +306,40 @@ impl Tile {
             TileType::Floor => false,
             TileType::ClosedDoor => true,
             TileType::OpenDoor => false,
+            TileType::StairsDown | TileType::StairsUp => false,
+            TileType::Brazier => false,
         }
     }
 
+    /// Whether an actor can step onto the tile as it is now, closed doors must be opened first.
+    pub fn is_walkable(&self) -> bool {
+        match self.tile_type {
+            TileType::Wall => false,
+            TileType::Floor => true,
+            TileType::ClosedDoor => false,
+            TileType::OpenDoor => true,
+            TileType::StairsDown | TileType::StairsUp => true,
+            TileType::Brazier => false,
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        matches!(self.visibility, Visibility::Visible)
+    }
+
+    /// Whether the player has ever seen the tile, whether or not it is in view now.
+    pub fn is_seen(&self) -> bool {
+        !matches!(self.visibility, Visibility::Hidden)
+    }
+
     pub fn movement_cost(&self) -> u32 {
         match self.tile_type {
             TileType::Wall => u32::MAX,
             TileType::Floor => 1,
             TileType::ClosedDoor => 5,
             TileType::OpenDoor => 1,
+            TileType::StairsDown | TileType::StairsUp => 1,
+            TileType::Brazier => u32::MAX,
         }
     }
 
@@ -164,23 +347,100 @@ impl Tile {
         match self.tile_type {
             TileType::Wall => unreachable!(),
             TileType::Floor => unreachable!(),
+            TileType::StairsDown | TileType::StairsUp => unreachable!(),
+            TileType::Brazier => unreachable!(),
             TileType::ClosedDoor => self.tile_type = TileType::OpenDoor,
             TileType::OpenDoor => self.tile_type = TileType::ClosedDoor,
         }
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TileType {
     Floor,
     Wall,
     ClosedDoor,
     OpenDoor,
+    StairsDown,
+    StairsUp,
+    /// A fire bowl that lights the tiles around it.
+    Brazier,
 }
 
-#[derive(Clone, Copy)]
+impl TileType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TileType::Floor => "floor",
+            TileType::Wall => "wall",
+            TileType::ClosedDoor => "closed door",
+            TileType::OpenDoor => "open door",
+            TileType::StairsDown => "stairs down",
+            TileType::StairsUp => "stairs up",
+            TileType::Brazier => "brazier",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub enum Visibility {
     Hidden,
     Visible,
     Explored,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map_generator::{GeneratorKind, carve_horizontal_tunnel, index, is_connected, solid_layout};
+    use rand::SeedableRng;
+
+    #[test]
+    fn braziers_light_their_surroundings_without_splitting_the_floor() {
+        for depth in [1, 12, 30] {
+            let mut rng = ChaCha12Rng::seed_from_u64(depth as u64);
+            let mut map = MapManager::new();
+            map.build_floor(depth, GeneratorKind::Bsp, &mut rng);
+
+            let braziers: Vec<Position> = (0..DUNGEON_SIZE * DUNGEON_SIZE)
+                .map(|i| Position {
+                    x: (i % DUNGEON_SIZE) as i64,
+                    y: (i / DUNGEON_SIZE) as i64,
+                })
+                .filter(|position| map.get_tile(*position).unwrap().tile_type == TileType::Brazier)
+                .collect();
+            assert!(braziers.len() >= MIN_BRAZIERS, "depth {depth} has {} braziers", braziers.len());
+            for brazier in &braziers {
+                assert!(map.is_lit(*brazier));
+                assert!(brazier.get_neighbours().into_iter().all(|(neighbour, _)| map.is_lit(neighbour)));
+            }
+
+            let layout: Vec<TileType> = map.tiles.iter().map(|tile| if tile.tile_type == TileType::Brazier { TileType::Wall } else { tile.tile_type }).collect();
+            assert!(is_connected(&layout), "depth {depth} is split by its braziers");
+        }
+    }
+
+    #[test]
+    fn nearest_free_tile_steps_past_everyone_crowding_the_stairs() {
+        let mut layout = solid_layout();
+        carve_horizontal_tunnel(&mut layout, 1, 20, 5);
+        layout[index(10, 5)] = TileType::StairsUp;
+        let mut map = MapManager::from_layout(&layout);
+        for (actor_id, x) in [(1, 8), (2, 9), (3, 10), (4, 11)] {
+            map.set_actor(Position { x, y: 5 }, actor_id);
+        }
+
+        assert_eq!(map.nearest_free_tile(Position { x: 10, y: 5 }), Some(Position { x: 12, y: 5 }));
+        assert_eq!(map.nearest_free_tile(Position { x: 15, y: 5 }), Some(Position { x: 15, y: 5 }));
+    }
+
+    #[test]
+    fn projectiles_fall_short_of_a_brazier() {
+        let mut layout = solid_layout();
+        carve_horizontal_tunnel(&mut layout, 1, 20, 5);
+        layout[index(10, 5)] = TileType::Brazier;
+        let map = MapManager::from_layout(&layout);
+
+        let path = map.trace_projectile(Position { x: 2, y: 5 }, Position { x: 10, y: 5 });
+        assert_eq!(path.last(), Some(&Position { x: 9, y: 5 }));
+    }
+}
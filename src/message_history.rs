@@ -0,0 +1,57 @@
+use crate::{GameData, GameState, playing::Playing};
+use crossterm::{
+    QueueableCommand,
+    cursor::MoveTo,
+    event::KeyCode,
+    style::{Color, Print, PrintStyledContent, Stylize, style},
+    terminal::{Clear, ClearType},
+};
+use std::io::Write;
+
+/// Full screen, scrollable view of every message in the log.
+pub struct MessageHistory {
+    /// How many lines the view is scrolled up from the most recent message.
+    scroll: usize,
+}
+
+impl MessageHistory {
+    pub fn new() -> Self {
+        Self { scroll: 0 }
+    }
+
+    pub fn update(mut self, game_data: &mut GameData) -> GameState {
+        let max_scroll = game_data.messages.messages().len().saturating_sub(1);
+        match game_data.input.last_key() {
+            KeyCode::Esc | KeyCode::Char('m') => return GameState::Playing(Playing::new()),
+            KeyCode::Up | KeyCode::Char('8') | KeyCode::Char('w') => self.scroll = (self.scroll + 1).min(max_scroll),
+            KeyCode::Down | KeyCode::Char('2') | KeyCode::Char('s') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::PageUp => self.scroll = (self.scroll + 10).min(max_scroll),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_sub(10),
+            _ => {}
+        }
+
+        GameState::MessageHistory(self)
+    }
+
+    pub fn draw(&self, game_data: &GameData) -> std::io::Result<()> {
+        let mut stdout = std::io::stdout();
+        stdout.queue(Clear(ClearType::All))?;
+
+        stdout.queue(MoveTo(0, 0))?;
+        stdout.queue(PrintStyledContent(style("Message history (up/down to scroll, Esc to close)").with(Color::Yellow)))?;
+
+        let (_, height) = crossterm::terminal::size()?;
+        let visible_lines = height.saturating_sub(2) as usize;
+        let messages = game_data.messages.messages();
+        let end = messages.len().saturating_sub(self.scroll);
+        let start = end.saturating_sub(visible_lines);
+
+        for (i, message) in messages[start..end].iter().enumerate() {
+            stdout.queue(MoveTo(0, 2 + i as u16))?;
+            stdout.queue(Print(message.display()))?;
+        }
+
+        stdout.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+const MAX_MESSAGES: usize = 500;
+
+#[derive(Serialize, Deserialize)]
+pub struct Message {
+    text: String,
+    count: u32,
+    turn: u64,
+}
+
+impl Message {
+    /// The message text, with a repeat count when the same message arrived several times in a row.
+    pub fn display(&self) -> String {
+        if self.count > 1 { format!("{} x{}", self.text, self.count) } else { self.text.clone() }
+    }
+
+    pub fn turn(&self) -> u64 {
+        self.turn
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MessageLog {
+    messages: Vec<Message>,
+}
+
+impl MessageLog {
+    pub fn new() -> Self {
+        Self { messages: Vec::new() }
+    }
+
+    pub fn add(&mut self, text: String, turn: u64) {
+        let text = capitalize(&text);
+
+        // fold repeats of the last message into a single line
+        if let Some(last) = self.messages.last_mut()
+            && last.text == text
+        {
+            last.count += 1;
+            last.turn = turn;
+            return;
+        }
+
+        self.messages.push(Message { text, count: 1, turn });
+        if self.messages.len() > MAX_MESSAGES {
+            self.messages.remove(0);
+        }
+    }
+
+    pub fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+
+    pub fn recent(&self, count: usize) -> &[Message] {
+        &self.messages[self.messages.len().saturating_sub(count)..]
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,50 @@
+use crate::{GameData, assets::item_name};
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Writes a plain text record of the dead character to disk and returns the path it was written to.
+pub fn write_morgue(game_data: &GameData) -> Result<String, String> {
+    let player = game_data.actors.get_player_actor();
+    let mut morgue = String::new();
+
+    // writing to a String cannot fail
+    let _ = writeln!(morgue, "Lair of the Demon King - morgue file");
+    let _ = writeln!(morgue);
+    let _ = writeln!(morgue, "{}, the level {} {}", player.name(), player.level(), player.title());
+    let _ = writeln!(morgue, "{} on depth {}", game_data.cause_of_death.as_deref().unwrap_or("Died"), game_data.depth);
+    let _ = writeln!(morgue, "Survived {} turns and killed {} monsters", game_data.turns, game_data.kills);
+    let _ = writeln!(morgue, "Seed: {}", game_data.seed);
+
+    let _ = writeln!(morgue);
+    let _ = writeln!(morgue, "Equipment:");
+    if let Some(equipment) = player.equipment() {
+        for (slot, item_id) in equipment.iter_slots() {
+            let item_name = item_id.as_deref().map(item_name).unwrap_or_else(|| "-".to_string());
+            let _ = writeln!(morgue, "  {:<8} {}", slot, item_name);
+        }
+    }
+
+    let _ = writeln!(morgue);
+    let _ = writeln!(morgue, "Inventory:");
+    match player.inventory() {
+        Some(inventory) if !inventory.items().is_empty() => {
+            for item in inventory.items() {
+                let _ = writeln!(morgue, "  {} x{}", item_name(&item.item_id), item.quantity);
+            }
+        }
+        _ => {
+            let _ = writeln!(morgue, "  (empty)");
+        }
+    }
+
+    let _ = writeln!(morgue);
+    let _ = writeln!(morgue, "Messages:");
+    for message in game_data.messages.messages() {
+        let _ = writeln!(morgue, "  {}", message.display());
+    }
+
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0);
+    let path = format!("morgue-{}.txt", timestamp);
+    std::fs::write(&path, morgue).map_err(|err| format!("Failed to write morgue file: {}", err))?;
+    Ok(path)
+}
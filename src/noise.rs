@@ -0,0 +1,77 @@
+use crate::{map_manager::MapManager, position::Position};
+use std::collections::{BinaryHeap, HashMap};
+
+/// Extra loudness lost passing through a wall or closed door, on top of the usual one per tile.
+const MUFFLING: i32 = 4;
+
+/// How loud a noise made at `origin` is at each tile it reaches. It loses one point of loudness per tile
+/// travelled and more through walls, so it carries down open corridors but barely leaks into the next room.
+pub fn spread(map: &MapManager, origin: Position, loudness: i32) -> HashMap<Position, i32> {
+    let mut volumes = HashMap::new();
+    let mut frontier = BinaryHeap::new();
+    frontier.push((loudness, origin.x, origin.y));
+
+    while let Some((volume, x, y)) = frontier.pop() {
+        let position = Position { x, y };
+        if volumes.contains_key(&position) {
+            continue;
+        }
+        volumes.insert(position, volume);
+
+        for (neighbour, _) in position.get_neighbours() {
+            let Some(tile) = map.get_tile(neighbour) else {
+                continue;
+            };
+            let neighbour_volume = volume - if tile.blocks_vision() { 1 + MUFFLING } else { 1 };
+            if neighbour_volume > 0 && !volumes.contains_key(&neighbour) {
+                frontier.push((neighbour_volume, neighbour.x, neighbour.y));
+            }
+        }
+    }
+    volumes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map_generator::{carve_horizontal_tunnel, index, solid_layout};
+    use crate::map_manager::TileType;
+
+    /// A corridor along y = 5 with the given tile part way along it at x = 10.
+    fn corridor_with(tile_type: TileType) -> MapManager {
+        let mut layout = solid_layout();
+        carve_horizontal_tunnel(&mut layout, 1, 20, 5);
+        layout[index(10, 5)] = tile_type;
+        MapManager::from_layout(&layout)
+    }
+
+    #[test]
+    fn noise_fades_one_point_per_open_tile() {
+        let volumes = spread(&corridor_with(TileType::Floor), Position { x: 1, y: 5 }, 12);
+        assert_eq!(volumes[&Position { x: 1, y: 5 }], 12);
+        assert_eq!(volumes[&Position { x: 6, y: 5 }], 7);
+        assert_eq!(volumes[&Position { x: 12, y: 5 }], 1);
+        assert!(!volumes.contains_key(&Position { x: 13, y: 5 }));
+    }
+
+    #[test]
+    fn walls_and_closed_doors_muffle_noise() {
+        let listener = Position { x: 12, y: 5 };
+        for tile_type in [TileType::Wall, TileType::ClosedDoor] {
+            let volumes = spread(&corridor_with(tile_type), Position { x: 1, y: 5 }, 12);
+            assert!(!volumes.contains_key(&listener));
+        }
+        let volumes = spread(&corridor_with(TileType::OpenDoor), Position { x: 1, y: 5 }, 12);
+        assert_eq!(volumes[&listener], 1);
+    }
+
+    #[test]
+    fn noise_carries_weakly_through_a_thin_wall() {
+        let mut layout = solid_layout();
+        carve_horizontal_tunnel(&mut layout, 1, 20, 5);
+        carve_horizontal_tunnel(&mut layout, 1, 20, 7);
+        let volumes = spread(&MapManager::from_layout(&layout), Position { x: 5, y: 5 }, 10);
+        assert_eq!(volumes[&Position { x: 5, y: 6 }], 10 - 1 - MUFFLING);
+        assert_eq!(volumes[&Position { x: 5, y: 7 }], 10 - 1 - MUFFLING - 1);
+    }
+}
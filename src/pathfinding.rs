@@ -1,4 +1,5 @@
 use crate::actor_manager::ActorManager;
+use crate::faction::FactionRelations;
 use crate::map_manager::MapManager;
 use crate::position::Position;
 use std::cmp::Ordering;
@@ -30,7 +31,7 @@ impl Ord for Node {
     }
 }
 
-pub fn a_star(actors: &ActorManager, map: &MapManager, start: Position, goal: Position, actor_id: usize) -> Option<Vec<Position>> {
+pub fn a_star(actors: &ActorManager, map: &MapManager, factions: &FactionRelations, start: Position, goal: Position, actor_id: usize) -> Option<Vec<Position>> {
     if start == goal {
         return Some(Vec::new());
     }
@@ -43,14 +44,11 @@ pub fn a_star(actors: &ActorManager, map: &MapManager, start: Position, goal: Po
     let mut nodes_visited = 0;
     const MAX_NODES: usize = 250;
 
-    g_scores.insert(start.clone(), 0.0);
+    g_scores.insert(start, 0.0);
 
-    let start_node = Node {
-        position: start.clone(),
-        f_score: start.octile_distance(goal),
-    };
+    let start_node = Node { position: start, f_score: start.octile_distance(goal) };
     open_set.push(start_node);
-    open_positions.insert(start.clone());
+    open_positions.insert(start);
 
     while let Some(current) = open_set.pop() {
         if nodes_visited > MAX_NODES {
@@ -59,7 +57,7 @@ pub fn a_star(actors: &ActorManager, map: &MapManager, start: Position, goal: Po
         nodes_visited += 1;
 
         open_positions.remove(&current.position);
-        closed_set.insert(current.position.clone());
+        closed_set.insert(current.position);
 
         // if the goal is found build and return the path
         if current.position == goal {
@@ -68,8 +66,8 @@ pub fn a_star(actors: &ActorManager, map: &MapManager, start: Position, goal: Po
 
             // Build path excluding the start position
             while let Some(prev) = came_from.get(&current_position) {
-                path.push(current_position.clone());
-                current_position = prev.clone();
+                path.push(current_position);
+                current_position = *prev;
             }
             path.reverse();
             return Some(path);
@@ -80,20 +78,29 @@ pub fn a_star(actors: &ActorManager, map: &MapManager, start: Position, goal: Po
                 continue;
             }
 
-            // check for obstacles
-            if let Some(other_actor_id) = map.get_tile(neighbour).unwrap().actor_id() {
-                if other_actor_id != actor_id && actors.get_actor(actor_id).unwrap().is_friendly_towards(&actors.get_actor(other_actor_id).unwrap()) {
-                    continue;
-                }
+            // walls can never be crossed
+            let Some(tile) = map.get_tile(neighbour) else {
+                continue;
+            };
+            if tile.movement_cost() == u32::MAX {
+                continue;
+            }
+
+            // anyone the actor would not attack is in the way
+            if let Some(other_actor_id) = tile.actor_id()
+                && other_actor_id != actor_id
+                && !actors.get_actor(actor_id).unwrap().is_hostile_towards(actors.get_actor(other_actor_id).unwrap(), factions)
+            {
+                continue;
             }
 
             // calculate the g score, which is the score to get from the start to this tile
-            let tentative_g_score = g_scores.get(&current.position).unwrap() + (map.get_tile(neighbour).unwrap().movement_cost() as f32 * movement_cost_multiplier);
+            let tentative_g_score = g_scores.get(&current.position).unwrap() + (tile.movement_cost() as f32 * movement_cost_multiplier);
 
             // if the g score is better or doesnt exist (we have never checked this path before or this path is better than the previous ones) add it
             if tentative_g_score < *g_scores.get(&neighbour).unwrap_or(&f32::MAX) {
-                came_from.insert(neighbour.clone(), current.position.clone());
-                g_scores.insert(neighbour.clone(), tentative_g_score);
+                came_from.insert(neighbour, current.position);
+                g_scores.insert(neighbour, tentative_g_score);
 
                 // add it to the open set if not already there
                 if !open_positions.contains(&neighbour) {
@@ -106,7 +113,7 @@ pub fn a_star(actors: &ActorManager, map: &MapManager, start: Position, goal: Po
                     let tie_breaker = cross * 0.001; // Small bias
 
                     let f_score = tentative_g_score + neighbour.octile_distance(goal) + tie_breaker;
-                    open_set.push(Node { position: neighbour.clone(), f_score });
+                    open_set.push(Node { position: neighbour, f_score });
                     open_positions.insert(neighbour);
                 }
             }
@@ -1,12 +1,47 @@
-use crate::{GameData, GameState, actor::ApplyDamageResult, main_menu::MainMenu, position::Position};
+use crate::{
+    GameData, GameState,
+    actor::{ActorAiState, ApplyDamageResult, AttackOutcome, Awareness},
+    assets::{ASSETS, ArmorSlot, ConsumableEffect, DamageType, ItemType, Relation, SpellTarget, item_name},
+    dice::Dice,
+    faction::faction_name,
+    game_over::GameOver,
+    hud::{self, SIDEBAR_WIDTH},
+    inventory::InventoryItem,
+    inventory_menu::InventoryMenu,
+    level_up_menu::LevelUpMenu,
+    main_menu::MainMenu,
+    map_manager::TileType,
+    message_history::MessageHistory,
+    morgue, noise,
+    position::Position,
+    save,
+    spell_menu::SpellMenu,
+    status_effect::StatusTick,
+    targeting::{Targeting, TargetingPurpose},
+};
 use crossterm::{
     QueueableCommand,
     cursor::MoveTo,
     event::KeyCode,
-    style::{Print, PrintStyledContent, Stylize, style},
+    style::{Color, Print, PrintStyledContent, Stylize, style},
+    terminal::{Clear, ClearType},
 };
+use rand::{Rng, seq::IndexedRandom};
 use std::io::Write;
 
+const MESSAGE_LINES: u16 = 4;
+const CONFUSED_STUMBLE_CHANCE: f64 = 0.5;
+const AMMO_RECOVERY_CHANCE: f64 = 0.5;
+/// How far, in open tiles, the noise of each kind of action carries.
+const FOOTSTEP_LOUDNESS: i32 = 2;
+const DOOR_LOUDNESS: i32 = 6;
+const COMBAT_LOUDNESS: i32 = 10;
+const BOWSTRING_LOUDNESS: i32 = 4;
+const SPELL_LOUDNESS: i32 = 6;
+const BURST_LOUDNESS: i32 = 12;
+/// Damage multiplier for hitting someone who never saw it coming.
+const SNEAK_ATTACK_MULTIPLIER: i32 = 2;
+
 #[derive(Clone, Copy)]
 pub struct Playing;
 
@@ -17,50 +52,153 @@ impl Playing {
 
     pub fn update(self, game_data: &mut GameData) -> GameState {
         // State changes
-        match game_data.input.last_key() {
-            KeyCode::Esc => return GameState::MainMenu(MainMenu::new()),
-            _ => {}
+        if game_data.input.last_key() == KeyCode::Esc {
+            return match save::save_game(game_data) {
+                Ok(()) => GameState::MainMenu(MainMenu::with_message("Game saved".to_string())),
+                Err(err) => GameState::MainMenu(MainMenu::with_message(err)),
+            };
+        }
+        if game_data.input.last_key() == KeyCode::Char('m') {
+            return GameState::MessageHistory(MessageHistory::new());
+        }
+        if game_data.input.last_key() == KeyCode::Char('i') {
+            return GameState::InventoryMenu(InventoryMenu::new());
+        }
+        if game_data.input.last_key() == KeyCode::Char('l') {
+            return Targeting::start(TargetingPurpose::Look, game_data);
+        }
+        if game_data.input.last_key() == KeyCode::Char('f') {
+            if let Err(err) = game_data.actors.get_player_actor().ranged_attack() {
+                game_data.log(format!("{}.", err));
+                return GameState::Playing(self);
+            }
+            return Targeting::start(TargetingPurpose::Fire, game_data);
+        }
+        if game_data.input.last_key() == KeyCode::Char('z') {
+            if game_data.actors.get_player_actor().known_spells().is_empty() {
+                game_data.log("You do not know any spells.".to_string());
+                return GameState::Playing(self);
+            }
+            return GameState::SpellMenu(SpellMenu::new());
         }
 
         // Turn handling
         if let Some(action) = self.handle_player_turn(game_data) {
-            self.process_action(0, action, game_data);
-            self.run_ai_turns(game_data);
-            game_data.map.update_visibility(game_data.actors.get_player_actor().position());
+            return self.take_player_turn(action, game_data);
         }
 
         GameState::Playing(self)
     }
 
+    /// Carries out the players action and lets everyone else act until it is the players turn again.
+    pub fn take_player_turn(self, action: Action, game_data: &mut GameData) -> GameState {
+        game_data.turns += 1;
+        self.process_action(0, action, game_data);
+        // update before monsters act so messages about them reflect what the player can now see
+        game_data.update_visibility();
+        self.run_ai_turns(game_data);
+
+        if game_data.cause_of_death.is_some() {
+            save::delete_save();
+            let morgue_result = morgue::write_morgue(game_data);
+            return GameState::GameOver(GameOver::new(game_data, morgue_result));
+        }
+
+        game_data.update_visibility();
+        if game_data.actors.get_player_actor().unspent_points() > 0 {
+            return GameState::LevelUpMenu(LevelUpMenu::new());
+        }
+        GameState::Playing(self)
+    }
+
     fn handle_player_turn(&self, game_data: &mut GameData) -> Option<Action> {
+        let player_position = game_data.actors.get_player_actor().position();
         match game_data.input.last_key() {
-            KeyCode::Char('8') => Some(Action::MoveTo(Position { x: 0, y: -1 } + game_data.actors.get_player_actor().position())),
-            KeyCode::Char('9') => Some(Action::MoveTo(Position { x: 1, y: -1 } + game_data.actors.get_player_actor().position())),
-            KeyCode::Char('6') => Some(Action::MoveTo(Position { x: 1, y: 0 } + game_data.actors.get_player_actor().position())),
-            KeyCode::Char('3') => Some(Action::MoveTo(Position { x: 1, y: 1 } + game_data.actors.get_player_actor().position())),
-            KeyCode::Char('2') => Some(Action::MoveTo(Position { x: 0, y: 1 } + game_data.actors.get_player_actor().position())),
-            KeyCode::Char('1') => Some(Action::MoveTo(Position { x: -1, y: 1 } + game_data.actors.get_player_actor().position())),
-            KeyCode::Char('4') => Some(Action::MoveTo(Position { x: -1, y: 0 } + game_data.actors.get_player_actor().position())),
-            KeyCode::Char('7') => Some(Action::MoveTo(Position { x: -1, y: -1 } + game_data.actors.get_player_actor().position())),
+            KeyCode::Char('8') => self.bump(Position { x: 0, y: -1 } + player_position, game_data),
+            KeyCode::Char('9') => self.bump(Position { x: 1, y: -1 } + player_position, game_data),
+            KeyCode::Char('6') => self.bump(Position { x: 1, y: 0 } + player_position, game_data),
+            KeyCode::Char('3') => self.bump(Position { x: 1, y: 1 } + player_position, game_data),
+            KeyCode::Char('2') => self.bump(Position { x: 0, y: 1 } + player_position, game_data),
+            KeyCode::Char('1') => self.bump(Position { x: -1, y: 1 } + player_position, game_data),
+            KeyCode::Char('4') => self.bump(Position { x: -1, y: 0 } + player_position, game_data),
+            KeyCode::Char('7') => self.bump(Position { x: -1, y: -1 } + player_position, game_data),
+            KeyCode::Char('g') | KeyCode::Char(',') => {
+                if game_data.map.get_tile(player_position).unwrap().items().is_empty() {
+                    game_data.log("There is nothing here to pick up.".to_string());
+                    None
+                } else {
+                    Some(Action::PickUp)
+                }
+            }
+            KeyCode::Char('>') => match game_data.map.get_tile(player_position).unwrap().tile_type() {
+                TileType::StairsDown => Some(Action::Descend),
+                _ => None,
+            },
+            KeyCode::Char('<') => match game_data.map.get_tile(player_position).unwrap().tile_type() {
+                TileType::StairsUp => Some(Action::Ascend),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Works out what moving the player into a tile should do. Returns None when nothing
+    /// sensible can happen there, so bumping into a wall or an ally does not cost a turn.
+    fn bump(&self, destination: Position, game_data: &GameData) -> Option<Action> {
+        let tile = game_data.map.get_tile(destination)?;
+
+        if let Some(other_actor_id) = tile.actor_id() {
+            let player = game_data.actors.get_player_actor();
+            let other_actor = game_data.actors.get_actor(other_actor_id)?;
+            return if player.is_friendly_towards(other_actor, &game_data.factions) { None } else { Some(Action::MeleeAttack(other_actor_id)) };
+        }
+
+        match tile.tile_type() {
+            TileType::ClosedDoor => Some(Action::Interact(destination)),
+            _ if tile.is_walkable() => Some(Action::MoveTo(destination)),
             _ => None,
         }
     }
 
     fn run_ai_turns(&self, game_data: &mut GameData) {
         loop {
+            if game_data.cause_of_death.is_some() {
+                return;
+            }
+
             let actor_id = game_data.actors.next_turn().unwrap();
             if actor_id == 0 {
                 return;
             }
 
-            // dummy ai turn: wait
-            self.process_action(actor_id, Action::Wait, game_data);
+            let actor = game_data.actors.get_actor(actor_id).unwrap();
+            let previous_state = actor.ai_state().clone();
+            let previous_awareness = actor.awareness();
+            let spotted = actor.spot_hostile(actor_id, &game_data.actors, &game_data.map, &game_data.factions, &mut game_data.rng);
+            game_data.actors.get_actor_mut(actor_id).unwrap().update_awareness(spotted, &mut game_data.rng);
 
-            // TODO implement AI logic
-            // let actor = game_data.actors.get_actor(actor_id).unwrap();
-            // let (actor_state, action) = actor.ai_turn(actor_id, &game_data.actors, &game_data.map);
-            // game_data.actors.get_actor_mut(actor_id).unwrap().set_state(actor_state);
-            // self.process_action(actor_id, action, game_data);
+            let actor = game_data.actors.get_actor(actor_id).unwrap();
+            let (actor_state, action) = actor.ai_turn(actor_id, &game_data.actors, &game_data.map, &game_data.factions, &mut game_data.rng);
+            let awareness = actor.awareness();
+
+            if player_can_see(game_data, actor.position()) {
+                let name = actor.definite_name();
+                if matches!(previous_state, ActorAiState::Sleeping) && !matches!(actor_state, ActorAiState::Sleeping) {
+                    game_data.log(format!("{} wakes up.", name));
+                }
+                if previous_awareness == Awareness::Unaware && awareness == Awareness::Suspicious {
+                    game_data.log(format!("{} looks around suspiciously.", name));
+                }
+                if !matches!(previous_state, ActorAiState::TargetingActor(0)) && matches!(actor_state, ActorAiState::TargetingActor(0)) {
+                    game_data.log(format!("{} notices you!", name));
+                }
+                if !matches!(previous_state, ActorAiState::Fleeing(_)) && matches!(actor_state, ActorAiState::Fleeing(_)) {
+                    game_data.log(format!("{} turns to flee!", name));
+                }
+            }
+
+            game_data.actors.get_actor_mut(actor_id).unwrap().set_state(actor_state);
+            self.process_action(actor_id, action, game_data);
         }
     }
 
@@ -68,70 +206,566 @@ impl Playing {
         let actor_speed = game_data.actors.get_actor(actor_id).unwrap().speed();
         let speed_modifier = (10000 / actor_speed).max(10);
         let cost = (action.cost() * speed_modifier) / 100;
-        game_data.actors.end_turn(cost);
+        let status_tick = game_data.actors.end_turn(cost);
+        if !self.handle_status_tick(actor_id, status_tick, game_data) {
+            return;
+        }
 
+        let action = self.confuse_action(actor_id, action, game_data);
         match action {
             Action::Wait => {
                 // Do nothing
             }
             Action::MoveTo(destination_position) => {
-                if let Some(actor) = game_data.actors.get_actor_mut(actor_id) {
-                    let current_position = actor.position();
+                // the way may have been blocked since the action was chosen, in which case the move is wasted
+                let actor = game_data.actors.get_actor_mut(actor_id).unwrap();
+                let current_position = actor.position();
+                let destination_free = game_data.map.get_tile(destination_position).is_some_and(|tile| tile.is_walkable() && tile.actor_id().is_none());
+                if current_position.is_adjacent(destination_position) && destination_free {
                     game_data.map.move_actor(current_position, destination_position);
                     actor.set_position(destination_position);
+                    self.make_noise(actor_id, destination_position, FOOTSTEP_LOUDNESS, "footsteps", game_data);
+
+                    if actor_id == 0 {
+                        let items = game_data.map.get_tile(destination_position).unwrap().items();
+                        if !items.is_empty() {
+                            let listing = describe_items(items.items());
+                            game_data.log(format!("You see here {}.", listing));
+                        }
+                    }
                 }
             }
-            Action::Interact(position) => game_data.map.get_tile_mut(position).unwrap().interact(),
+            Action::Interact(position) => {
+                if let Some(tile) = game_data.map.get_tile_mut(position)
+                    && matches!(tile.tile_type(), TileType::ClosedDoor | TileType::OpenDoor)
+                    && tile.actor_id().is_none()
+                {
+                    tile.interact();
+                    let opened = tile.tile_type() == TileType::OpenDoor;
+                    self.make_noise(actor_id, position, DOOR_LOUDNESS, "a door", game_data);
+                    if actor_id == 0 || player_can_see(game_data, position) {
+                        let verb = match (actor_id == 0, opened) {
+                            (true, true) => "open",
+                            (true, false) => "close",
+                            (false, true) => "opens",
+                            (false, false) => "closes",
+                        };
+                        game_data.log(format!("{} {} the door.", actor_name(game_data, actor_id), verb));
+                    }
+                }
+            }
+            Action::PickUp => {
+                let position = game_data.actors.get_actor(actor_id).unwrap().position();
+                if game_data.actors.get_actor(actor_id).unwrap().inventory().is_none() {
+                    return;
+                }
+
+                let items = game_data.map.get_tile_mut(position).unwrap().items_mut().take_all();
+                if items.is_empty() {
+                    return;
+                }
+                if actor_id == 0 || player_can_see(game_data, position) {
+                    let verb = if actor_id == 0 { "pick" } else { "picks" };
+                    game_data.log(format!("{} {} up {}.", actor_name(game_data, actor_id), verb, describe_items(&items)));
+                }
+
+                let actor = game_data.actors.get_actor_mut(actor_id).unwrap();
+                for item in items {
+                    actor.add_item(item.item_id, item.quantity).unwrap();
+                }
+            }
+            Action::Drop(item_id) => {
+                let actor = game_data.actors.get_actor_mut(actor_id).unwrap();
+                let position = actor.position();
+                let quantity = actor.inventory().map(|inventory| inventory.get_quantity(&item_id)).unwrap_or(0);
+                if quantity == 0 || !actor.remove_item(&item_id, quantity).unwrap_or(false) {
+                    return;
+                }
+
+                let dropped = InventoryItem { item_id, quantity };
+                if actor_id == 0 || player_can_see(game_data, position) {
+                    let verb = if actor_id == 0 { "drop" } else { "drops" };
+                    game_data.log(format!("{} {} {}.", actor_name(game_data, actor_id), verb, describe_items(std::slice::from_ref(&dropped))));
+                }
+                game_data.map.get_tile_mut(position).unwrap().items_mut().add_item(dropped.item_id, dropped.quantity);
+            }
+            Action::Equip(item_id) => {
+                let result = game_data.actors.get_actor_mut(actor_id).unwrap().equip_item_from_inventory(&item_id);
+                if actor_id == 0 {
+                    match result {
+                        Ok(()) => game_data.log(format!("You equip the {}.", item_name(&item_id))),
+                        Err(err) => game_data.log(format!("You cannot equip the {}: {}.", item_name(&item_id), err.to_lowercase())),
+                    }
+                }
+            }
+            Action::UnequipWeapon => {
+                if let Some(item_id) = game_data.actors.get_actor_mut(actor_id).unwrap().unequip_weapon()
+                    && actor_id == 0
+                {
+                    game_data.log(format!("You unwield the {}.", item_name(&item_id)));
+                }
+            }
+            Action::UnequipArmor(slot) => {
+                if let Some(item_id) = game_data.actors.get_actor_mut(actor_id).unwrap().unequip_armor_slot(&slot)
+                    && actor_id == 0
+                {
+                    game_data.log(format!("You take off the {}.", item_name(&item_id)));
+                }
+            }
+            Action::UseItem(item_id) => {
+                let result = game_data.actors.get_actor_mut(actor_id).unwrap().use_consumable(&item_id);
+                if actor_id == 0 {
+                    match &result {
+                        Ok(effect_message) => game_data.log(format!("You use the {}. {}.", item_name(&item_id), effect_message)),
+                        Err(err) => game_data.log(format!("You cannot use the {}: {}.", item_name(&item_id), err.to_lowercase())),
+                    }
+                }
+
+                if result.is_ok()
+                    && let Some(ItemType::Consumable {
+                        effect: ConsumableEffect::Burst { damage, damage_type, radius },
+                    }) = ASSETS.item_kinds.iter().find(|kind| kind.id == item_id).map(|kind| &kind.item_type)
+                {
+                    let user_position = game_data.actors.get_actor(actor_id).unwrap().position();
+                    self.make_noise(actor_id, user_position, BURST_LOUDNESS, "a blast", game_data);
+                    for position in blast_area(game_data, user_position, *radius) {
+                        if let Some(target_actor_id) = game_data.map.get_tile(position).and_then(|tile| tile.actor_id())
+                            && target_actor_id != actor_id
+                        {
+                            self.provoke(actor_id, target_actor_id, game_data);
+                            self.blast_damage(actor_id, target_actor_id, *damage, *damage_type, &item_name(&item_id), game_data);
+                        }
+                    }
+                }
+            }
+            Action::Descend => {
+                game_data.change_level(game_data.depth + 1);
+                game_data.log(format!("You descend to depth {}.", game_data.depth));
+            }
+            Action::Ascend => {
+                game_data.change_level(game_data.depth - 1);
+                game_data.log(format!("You climb up to depth {}.", game_data.depth));
+            }
+            Action::CastSpell(spell_id, target_position) => self.cast_spell(actor_id, &spell_id, target_position, game_data),
+            Action::RangedAttack(target_position) => self.ranged_attack(actor_id, target_position, game_data),
             Action::MeleeAttack(target_actor_id) => {
-                let attack_roll = game_data.actors.get_actor(actor_id).unwrap().melee_attack_roll();
-                let result = game_data.actors.get_actor_mut(target_actor_id).unwrap().apply_damage(attack_roll);
-                match result {
-                    ApplyDamageResult::None => {}
-                    ApplyDamageResult::ActorDied => {
-                        let target_actor_position = game_data.actors.get_actor_mut(target_actor_id).unwrap().position();
-                        game_data.actors.remove_actor(target_actor_id);
-                        game_data.map.remove_actor(target_actor_position);
-                        // TODO player death to game over screen
+                // the target may have died or moved away since the attack was chosen
+                let attacker_position = game_data.actors.get_actor(actor_id).unwrap().position();
+                if !game_data.actors.get_actor(target_actor_id).is_some_and(|target| target.position().is_adjacent(attacker_position)) {
+                    return;
+                }
+
+                let target_actor_position = game_data.actors.get_actor(target_actor_id).unwrap().position();
+                self.melee_attack(actor_id, target_actor_id, game_data);
+                self.make_noise(actor_id, target_actor_position, COMBAT_LOUDNESS, "fighting", game_data);
+            }
+        }
+    }
+
+    /// Swings at an adjacent target, rolling to hit and applying the damage and any on-hit effects.
+    fn melee_attack(&self, actor_id: usize, target_actor_id: usize, game_data: &mut GameData) {
+        let target_awareness = game_data.actors.get_actor(target_actor_id).unwrap().awareness();
+        self.provoke(actor_id, target_actor_id, game_data);
+        let attacker_name = actor_name(game_data, actor_id);
+        let target_name = actor_name(game_data, target_actor_id);
+        let attacker_position = game_data.actors.get_actor(actor_id).unwrap().position();
+        let target_actor_position = game_data.actors.get_actor(target_actor_id).unwrap().position();
+        let involves_player = actor_id == 0 || target_actor_id == 0;
+        let visible = involves_player || player_can_see(game_data, attacker_position) || player_can_see(game_data, target_actor_position);
+
+        let attacker = game_data.actors.get_actor(actor_id).unwrap();
+        let target = game_data.actors.get_actor(target_actor_id).unwrap();
+        let (melee_damage, damage_type) = attacker.melee_damage();
+        let outcome = attacker.attack_roll(target, melee_damage, &mut game_data.rng);
+        let (AttackOutcome::Hit(attack_roll) | AttackOutcome::Crit(attack_roll)) = outcome else {
+            if visible {
+                let verb = if actor_id == 0 { "miss" } else { "misses" };
+                game_data.log(format!("{} {} {}.", attacker_name, verb, target_name));
+            }
+            return;
+        };
+        let attack_roll = self.sneak_attack(actor_id, target_actor_id, target_awareness, attack_roll, visible, game_data);
+        let breakdown = game_data.actors.get_actor_mut(target_actor_id).unwrap().apply_damage(attack_roll, damage_type, &mut game_data.rng);
+        if visible {
+            let verb = if actor_id == 0 { "hit" } else { "hits" };
+            match outcome {
+                AttackOutcome::Crit(_) => game_data.log(format!("{} critically {} {} for {}!", attacker_name, verb, target_name, breakdown.describe())),
+                _ => game_data.log(format!("{} {} {} for {}.", attacker_name, verb, target_name, breakdown.describe())),
+            }
+        }
+
+        if let ApplyDamageResult::ActorDied = breakdown.result {
+            let cause_of_death = format!("Killed by {}", game_data.actors.get_actor(actor_id).unwrap().indefinite_name());
+            self.handle_death(target_actor_id, Some(actor_id), cause_of_death, visible, game_data);
+            return;
+        }
+
+        for attack_effect in game_data.actors.get_actor(actor_id).unwrap().attack_effects() {
+            if !game_data.rng.random_bool(attack_effect.chance) {
+                continue;
+            }
+            let target_actor = game_data.actors.get_actor_mut(target_actor_id).unwrap();
+            if let Ok(kind) = target_actor.add_status_effect(&attack_effect.effect, attack_effect.magnitude, attack_effect.duration)
+                && visible
+            {
+                let verb = if target_actor_id == 0 { "are" } else { "is" };
+                game_data.log(format!("{} {} {}.", target_name, verb, kind.adjective));
+            }
+        }
+    }
+
+    fn ranged_attack(&self, actor_id: usize, target_position: Position, game_data: &mut GameData) {
+        let attacker = game_data.actors.get_actor(actor_id).unwrap();
+        let attacker_position = attacker.position();
+        let ranged_attack = match attacker.ranged_attack() {
+            Ok(ranged_attack) => ranged_attack,
+            Err(err) => {
+                if actor_id == 0 {
+                    game_data.log(format!("{}.", err));
+                }
+                return;
+            }
+        };
+        if attacker_position.chebyshev_distance(target_position) > ranged_attack.range {
+            if actor_id == 0 {
+                game_data.log("That is out of range.".to_string());
+            }
+            return;
+        }
+
+        let attacker_name = actor_name(game_data, actor_id);
+        let cause_of_death = format!("Shot by {}", attacker.indefinite_name());
+        if let Some(ammo_id) = &ranged_attack.ammo_id {
+            let _ = game_data.actors.get_actor_mut(actor_id).unwrap().remove_item(ammo_id, 1);
+        }
+
+        let path = game_data.map.trace_projectile(attacker_position, target_position);
+        let landing_position = path.last().copied().unwrap_or(attacker_position);
+        let target_actor_id = game_data.map.get_tile(landing_position).and_then(|tile| tile.actor_id()).filter(|id| *id != actor_id);
+        let visible = actor_id == 0 || target_actor_id == Some(0) || player_can_see(game_data, attacker_position) || player_can_see(game_data, landing_position);
+
+        match target_actor_id {
+            Some(target_actor_id) => {
+                let target_awareness = game_data.actors.get_actor(target_actor_id).unwrap().awareness();
+                self.provoke(actor_id, target_actor_id, game_data);
+                let target_name = actor_name(game_data, target_actor_id);
+                let attacker = game_data.actors.get_actor(actor_id).unwrap();
+                let target = game_data.actors.get_actor(target_actor_id).unwrap();
+                let outcome = attacker.attack_roll(target, ranged_attack.damage, &mut game_data.rng);
+                let verb = if actor_id == 0 { "shoot" } else { "shoots" };
+                match outcome {
+                    AttackOutcome::Miss => {
+                        if visible {
+                            game_data.log(format!("{} {} at {} and {}.", attacker_name, verb, target_name, if actor_id == 0 { "miss" } else { "misses" }));
+                        }
                     }
+                    AttackOutcome::Hit(attack_roll) | AttackOutcome::Crit(attack_roll) => {
+                        let attack_roll = self.sneak_attack(actor_id, target_actor_id, target_awareness, attack_roll, visible, game_data);
+                        let breakdown = game_data.actors.get_actor_mut(target_actor_id).unwrap().apply_damage(attack_roll, ranged_attack.damage_type, &mut game_data.rng);
+                        if visible {
+                            match outcome {
+                                AttackOutcome::Crit(_) => game_data.log(format!("{} {} {} critically for {}!", attacker_name, verb, target_name, breakdown.describe())),
+                                _ => game_data.log(format!("{} {} {} for {}.", attacker_name, verb, target_name, breakdown.describe())),
+                            }
+                        }
+                        if let ApplyDamageResult::ActorDied = breakdown.result {
+                            self.handle_death(target_actor_id, Some(actor_id), cause_of_death, visible, game_data);
+                        }
+                    }
+                }
+            }
+            None if visible => {
+                let verb = if actor_id == 0 { "shoot and hit" } else { "shoots and hits" };
+                game_data.log(format!("{} {} nothing.", attacker_name, verb));
+            }
+            None => {}
+        }
+        self.recover_ammo(ranged_attack.ammo_id, landing_position, game_data);
+        self.make_noise(actor_id, attacker_position, BOWSTRING_LOUDNESS, "a bowstring", game_data);
+    }
+
+    /// Multiplies the damage of a hit landed on a target that was unaware of the attacker, going by the
+    /// target's awareness from before the attack made any noise.
+    fn sneak_attack(&self, actor_id: usize, target_actor_id: usize, target_awareness: Awareness, damage: i32, visible: bool, game_data: &mut GameData) -> i32 {
+        if target_awareness != Awareness::Unaware {
+            return damage;
+        }
+        if visible {
+            let attacker_name = actor_name(game_data, actor_id);
+            let target_name = actor_name(game_data, target_actor_id);
+            let verb = if actor_id == 0 { "catch" } else { "catches" };
+            game_data.log(format!("{} {} {} unawares!", attacker_name, verb, target_name));
+        }
+        damage * SNEAK_ATTACK_MULTIPLIER
+    }
+
+    /// Fired ammunition can sometimes be picked up again where it fell.
+    fn recover_ammo(&self, ammo_id: Option<String>, landing_position: Position, game_data: &mut GameData) {
+        if let Some(ammo_id) = ammo_id
+            && game_data.rng.random_bool(AMMO_RECOVERY_CHANCE)
+        {
+            game_data.map.get_tile_mut(landing_position).unwrap().items_mut().add_item(ammo_id, 1);
+        }
+    }
+
+    /// Lets everyone within earshot of a noise react to it: monsters go to look unless they are allied with whoever made it,
+    /// and the player is told what they heard when they cannot see where it came from.
+    fn make_noise(&self, source_id: usize, position: Position, loudness: i32, description: &str, game_data: &mut GameData) {
+        let Some(source) = game_data.actors.get_actor(source_id) else {
+            return;
+        };
+        let mut listeners = Vec::new();
+        for (heard_position, volume) in noise::spread(&game_data.map, position, loudness) {
+            if let Some(listener_id) = game_data.map.get_tile(heard_position).and_then(|tile| tile.actor_id())
+                && listener_id != source_id
+                && !game_data.actors.get_actor(listener_id).unwrap().is_friendly_towards(source, &game_data.factions)
+            {
+                listeners.push((listener_id, volume));
+            }
+        }
+
+        for (listener_id, volume) in listeners {
+            if listener_id == 0 {
+                if !player_can_see(game_data, position) {
+                    let direction = game_data.actors.get_player_actor().position().direction_to(position);
+                    game_data.log(format!("You hear {} to the {}.", description, direction));
+                }
+                continue;
+            }
+            game_data.actors.get_actor_mut(listener_id).unwrap().hear_noise(position, volume);
+        }
+    }
+
+    /// Attacking someone whose faction was not already hostile makes the two factions enemies.
+    fn provoke(&self, attacker_id: usize, target_actor_id: usize, game_data: &mut GameData) {
+        let attacker = game_data.actors.get_actor(attacker_id).unwrap();
+        let target = game_data.actors.get_actor(target_actor_id).unwrap();
+        if attacker.faction() == target.faction() || target.is_hostile_towards(attacker, &game_data.factions) {
+            return;
+        }
+
+        let attacker_faction = attacker.faction().to_string();
+        let target_faction = target.faction().to_string();
+        game_data.factions.set_mutual_relation(&attacker_faction, &target_faction, Relation::Hostile);
+        if attacker_id == 0 {
+            game_data.log(format!("The {} turn hostile!", faction_name(&target_faction).to_lowercase()));
+        }
+    }
+
+    /// Rolls and deals damage from a spell or burst to one actor caught in it, returning whether they died.
+    fn blast_damage(&self, attacker_id: usize, target_actor_id: usize, damage: Dice, damage_type: DamageType, source_name: &str, game_data: &mut GameData) -> bool {
+        let cause_of_death = format!("Killed by {}", game_data.actors.get_actor(attacker_id).unwrap().indefinite_name());
+        let target_name = actor_name(game_data, target_actor_id);
+        let target_position = game_data.actors.get_actor(target_actor_id).unwrap().position();
+        let visible = attacker_id == 0 || target_actor_id == 0 || player_can_see(game_data, target_position);
+
+        let damage = damage.roll(&mut game_data.rng);
+        let breakdown = game_data.actors.get_actor_mut(target_actor_id).unwrap().apply_damage(damage, damage_type, &mut game_data.rng);
+        if visible {
+            game_data.log(format!("The {} hits {} for {}.", source_name.to_lowercase(), target_name, breakdown.describe()));
+        }
+        if let ApplyDamageResult::ActorDied = breakdown.result {
+            self.handle_death(target_actor_id, Some(attacker_id), cause_of_death, visible, game_data);
+            return true;
+        }
+        false
+    }
+
+    fn cast_spell(&self, actor_id: usize, spell_id: &str, target_position: Position, game_data: &mut GameData) {
+        let caster = game_data.actors.get_actor(actor_id).unwrap();
+        let caster_position = caster.position();
+        let Some(spell) = caster.known_spells().into_iter().find(|spell| spell.id == spell_id) else {
+            if actor_id == 0 {
+                game_data.log("You do not know that spell.".to_string());
+            }
+            return;
+        };
+
+        let target_position = if spell.target == SpellTarget::Caster { caster_position } else { target_position };
+        let in_range = caster_position.chebyshev_distance(target_position) <= spell.range && game_data.map.shadowcast(caster_position, spell.range).contains(&target_position);
+        if spell.target == SpellTarget::Other && !in_range {
+            if actor_id == 0 {
+                game_data.log("That is out of range.".to_string());
+            }
+            return;
+        }
+
+        let damage = caster.spell_damage(spell);
+        let caster_name = actor_name(game_data, actor_id);
+        if let Err(err) = game_data.actors.get_actor_mut(actor_id).unwrap().spend_mana(spell.mana_cost) {
+            if actor_id == 0 {
+                game_data.log(format!("You cannot cast {}: {}.", spell.name, err.to_lowercase()));
+            }
+            return;
+        }
+
+        if actor_id == 0 || player_can_see(game_data, caster_position) {
+            let verb = if actor_id == 0 { "cast" } else { "casts" };
+            game_data.log(format!("{} {} {}.", caster_name, verb, spell.name));
+        }
+        self.make_noise(actor_id, caster_position, SPELL_LOUDNESS, "chanting", game_data);
+
+        for position in blast_area(game_data, target_position, spell.area) {
+            let Some(target_actor_id) = game_data.map.get_tile(position).and_then(|tile| tile.actor_id()) else {
+                continue;
+            };
+            if spell.target == SpellTarget::Other && target_actor_id == actor_id {
+                continue;
+            }
+            if spell.target == SpellTarget::Other {
+                self.provoke(actor_id, target_actor_id, game_data);
+            }
+
+            let target_name = actor_name(game_data, target_actor_id);
+            let visible = actor_id == 0 || target_actor_id == 0 || player_can_see(game_data, position);
+
+            if spell.heal > 0 {
+                let healed = game_data.actors.get_actor_mut(target_actor_id).unwrap().heal(spell.heal);
+                if visible {
+                    let verb = if target_actor_id == 0 { "are" } else { "is" };
+                    game_data.log(format!("{} {} healed for {}.", target_name, verb, healed));
                 }
+            }
+
+            if damage.max() > 0 && self.blast_damage(actor_id, target_actor_id, damage, spell.damage_type, &spell.name, game_data) {
+                continue;
+            }
 
-                println!("Actor {} attacked Actor {} for {} damage", actor_id, target_actor_id, attack_roll);
+            if let Some(effect) = &spell.effect
+                && game_data.rng.random_bool(effect.chance)
+                && let Ok(kind) = game_data.actors.get_actor_mut(target_actor_id).unwrap().add_status_effect(&effect.effect, effect.magnitude, effect.duration)
+                && visible
+            {
+                let verb = if target_actor_id == 0 { "are" } else { "is" };
+                game_data.log(format!("{} {} {}.", target_name, verb, kind.adjective));
             }
         }
     }
 
+    /// Reports what the actor's status effects did during its turn. Returns false if they killed it.
+    fn handle_status_tick(&self, actor_id: usize, status_tick: StatusTick, game_data: &mut GameData) -> bool {
+        let position = game_data.actors.get_actor(actor_id).unwrap().position();
+        let visible = actor_id == 0 || player_can_see(game_data, position);
+        let name = actor_name(game_data, actor_id);
+
+        if visible {
+            for (kind, change) in &status_tick.health_changes {
+                if *change < 0 {
+                    let verb = if actor_id == 0 { "take" } else { "takes" };
+                    game_data.log(format!("{} {} {} {} damage.", name, verb, -change, kind.name.to_lowercase()));
+                } else {
+                    let verb = if actor_id == 0 { "recover" } else { "recovers" };
+                    game_data.log(format!("{} {} {} health.", name, verb, change));
+                }
+            }
+        }
+
+        if let Some(kind) = status_tick.killed_by {
+            let cause_of_death = format!("Killed by {}", kind.name.to_lowercase());
+            self.handle_death(actor_id, None, cause_of_death, visible, game_data);
+            return false;
+        }
+
+        if visible {
+            for kind in &status_tick.expired {
+                let verb = if actor_id == 0 { "are" } else { "is" };
+                game_data.log(format!("{} {} no longer {}.", name, verb, kind.adjective));
+            }
+        }
+        true
+    }
+
+    /// Confused actors sometimes lurch in a random direction instead of moving or attacking where they meant to.
+    fn confuse_action(&self, actor_id: usize, action: Action, game_data: &mut GameData) -> Action {
+        let actor = game_data.actors.get_actor(actor_id).unwrap();
+        if !matches!(action, Action::MoveTo(_) | Action::MeleeAttack(_)) || !actor.is_confused() || !game_data.rng.random_bool(CONFUSED_STUMBLE_CHANCE) {
+            return action;
+        }
+
+        let position = actor.position();
+        let neighbours = position.get_neighbours();
+        let (destination, _) = neighbours.choose(&mut game_data.rng).unwrap();
+        if player_can_see(game_data, position) {
+            let name = actor_name(game_data, actor_id);
+            let verb = if actor_id == 0 { "stumble" } else { "stumbles" };
+            game_data.log(format!("{} {} around in confusion.", name, verb));
+        }
+
+        match game_data.map.get_tile(*destination) {
+            Some(tile) if tile.actor_id().is_some() => Action::MeleeAttack(tile.actor_id().unwrap()),
+            Some(tile) if tile.is_walkable() => Action::MoveTo(*destination),
+            _ => Action::Wait,
+        }
+    }
+
+    /// Deals with a dead actor, ending the game for the player or dropping a monster's items and rewarding whoever killed it.
+    fn handle_death(&self, target_actor_id: usize, killer_id: Option<usize>, cause_of_death: String, visible: bool, game_data: &mut GameData) {
+        if target_actor_id == 0 {
+            // the player stays in place so the game over screen and morgue can still read them
+            game_data.cause_of_death = Some(cause_of_death);
+            game_data.log("You die...".to_string());
+            return;
+        }
+
+        let target_name = actor_name(game_data, target_actor_id);
+        let target_actor = game_data.actors.get_actor_mut(target_actor_id).unwrap();
+        let target_actor_position = target_actor.position();
+        let experience = target_actor.experience_value();
+        let dropped_items = target_actor.take_dropped_items(&mut game_data.rng);
+        let floor_items = game_data.map.get_tile_mut(target_actor_position).unwrap().items_mut();
+        for item in dropped_items {
+            floor_items.add_item(item.item_id, item.quantity);
+        }
+
+        game_data.actors.remove_actor(target_actor_id);
+        game_data.map.remove_actor(target_actor_position);
+        if killer_id == Some(0) {
+            game_data.kills += 1;
+            game_data.log(format!("You kill {}.", target_name));
+        } else if visible {
+            game_data.log(format!("{} dies.", target_name));
+        }
+
+        if let Some(killer) = killer_id.and_then(|killer_id| game_data.actors.get_actor_mut(killer_id))
+            && killer.gain_experience(experience) > 0
+            && killer_id == Some(0)
+        {
+            let level = killer.level();
+            game_data.log(format!("Welcome to level {}!", level));
+        }
+    }
+
     pub fn draw(&self, game_data: &GameData) -> std::io::Result<()> {
         let mut stdout = std::io::stdout();
         // stdout.queue(Clear(ClearType::All))?;
 
         let player_position = game_data.actors.get_player_actor().position();
-        let (width, height) = crossterm::terminal::size()?;
+        let (width, height) = viewport_size()?;
 
         for y in 0..height {
             for x in 0..width {
                 let map_x = player_position.x + x as i64 - (width / 2) as i64;
                 let map_y = player_position.y + y as i64 - (height / 2) as i64;
 
-                if let Some(tile) = game_data.map.get_tile(Position { x: map_x as i64, y: map_y as i64 }) {
-                    // Draw actor if present
-                    if let Some(actor_id) = tile.actor_id() {
-                        if let Some(actor) = game_data.actors.get_actor(actor_id) {
-                            let (ch, color) = actor.glyph();
-                            stdout.queue(MoveTo(x, y))?;
-                            stdout.queue(PrintStyledContent(style(ch).with(color)))?;
-                            continue;
-                        }
-                    }
-
-                    // Draw tile
-                    let (ch, color) = tile.glyph();
-                    stdout.queue(MoveTo(x, y))?;
-                    stdout.queue(PrintStyledContent(style(ch).with(color)))?;
-                } else {
+                stdout.queue(MoveTo(x, y))?;
+                match map_glyph(game_data, Position { x: map_x, y: map_y }) {
+                    Some((ch, color)) => stdout.queue(PrintStyledContent(style(ch).with(color)))?,
                     // draw empty space for out-of-bounds
-                    stdout.queue(MoveTo(x, y))?;
-                    stdout.queue(Print(' '))?;
-                }
+                    None => stdout.queue(Print(' '))?,
+                };
+            }
+        }
+
+        hud::draw_sidebar(&mut stdout, game_data, width, height)?;
+
+        // recent messages, the ones from this turn highlighted
+        let recent_messages = game_data.messages.recent(MESSAGE_LINES as usize);
+        for i in 0..MESSAGE_LINES {
+            stdout.queue(MoveTo(0, height + i))?;
+            stdout.queue(Clear(ClearType::CurrentLine))?;
+            if let Some(message) = recent_messages.get(i as usize) {
+                let color = if message.turn() == game_data.turns { Color::White } else { Color::DarkGrey };
+                stdout.queue(PrintStyledContent(style(message.display()).with(color)))?;
             }
         }
 
@@ -140,11 +774,90 @@ impl Playing {
     }
 }
 
+/// Size of the map viewport, which shrinks to leave room for the sidebar and the message lines.
+fn viewport_size() -> std::io::Result<(u16, u16)> {
+    let (screen_width, screen_height) = crossterm::terminal::size()?;
+    Ok((screen_width.saturating_sub(SIDEBAR_WIDTH), screen_height.saturating_sub(MESSAGE_LINES)))
+}
+
+/// Where a map position is drawn on screen, if it is inside the viewport.
+pub fn screen_position(game_data: &GameData, position: Position) -> std::io::Result<Option<(u16, u16)>> {
+    let player_position = game_data.actors.get_player_actor().position();
+    let (width, height) = viewport_size()?;
+    let x = position.x - player_position.x + (width / 2) as i64;
+    let y = position.y - player_position.y + (height / 2) as i64;
+    if (0..width as i64).contains(&x) && (0..height as i64).contains(&y) { Ok(Some((x as u16, y as u16))) } else { Ok(None) }
+}
+
+/// What is drawn for a map position: an actor in view, else the top item of any pile, else the tile itself.
+pub fn map_glyph(game_data: &GameData, position: Position) -> Option<(char, Color)> {
+    let tile = game_data.map.get_tile(position)?;
+    if tile.is_visible()
+        && let Some(actor_id) = tile.actor_id()
+        && let Some(actor) = game_data.actors.get_actor(actor_id)
+    {
+        return Some(actor.glyph());
+    }
+    Some(tile.item_glyph().unwrap_or_else(|| tile.glyph()))
+}
+
+/// Writes prompt lines over the oldest message lines, for screens drawn on top of the map.
+pub fn draw_prompt(stdout: &mut std::io::Stdout, lines: &[String]) -> std::io::Result<()> {
+    let (_, height) = viewport_size()?;
+    for (i, line) in lines.iter().take(MESSAGE_LINES as usize).enumerate() {
+        stdout.queue(MoveTo(0, height + i as u16))?;
+        stdout.queue(Clear(ClearType::CurrentLine))?;
+        stdout.queue(PrintStyledContent(style(line).with(Color::Yellow)))?;
+    }
+    Ok(())
+}
+
+/// Everything within `area` tiles of the center that a blast there can reach, or just the center for an area of 0.
+fn blast_area(game_data: &GameData, center: Position, area: i64) -> Vec<Position> {
+    if area <= 0 {
+        return vec![center];
+    }
+    game_data.map.shadowcast(center, area).into_iter().filter(|position| position.chebyshev_distance(center) <= area).collect()
+}
+
+/// How an actor is referred to in messages, "you" for the player.
+fn actor_name(game_data: &GameData, actor_id: usize) -> String {
+    if actor_id == 0 {
+        return "you".to_string();
+    }
+    match game_data.actors.get_actor(actor_id) {
+        Some(actor) => actor.definite_name(),
+        None => "something".to_string(),
+    }
+}
+
+/// Lists items for a message, such as "Iron Sword, Healing Potion x2".
+fn describe_items(items: &[InventoryItem]) -> String {
+    let names: Vec<String> = items.iter().map(|item| if item.quantity > 1 { format!("{} x{}", item_name(&item.item_id), item.quantity) } else { item_name(&item.item_id) }).collect();
+    names.join(", ")
+}
+
+fn player_can_see(game_data: &GameData, position: Position) -> bool {
+    game_data.map.get_tile(position).is_some_and(|tile| tile.is_visible())
+}
+
 pub enum Action {
     Wait,
     MoveTo(Position),
     Interact(Position),
     MeleeAttack(usize),
+    PickUp,
+    Drop(String),
+    Equip(String),
+    UnequipWeapon,
+    UnequipArmor(ArmorSlot),
+    UseItem(String),
+    Descend,
+    Ascend,
+    /// Casts the spell with the given id at a position, ignored for spells that target the caster.
+    CastSpell(String, Position),
+    /// Shoots towards a position, hitting the first actor in the way.
+    RangedAttack(Position),
 }
 
 impl Action {
@@ -154,6 +867,98 @@ impl Action {
             Action::MoveTo(_) => 100,
             Action::Interact(_) => 100,
             Action::MeleeAttack(_) => 100,
+            Action::PickUp => 50,
+            Action::Drop(_) => 50,
+            Action::Equip(_) => 100,
+            Action::UnequipWeapon => 100,
+            Action::UnequipArmor(_) => 100,
+            Action::UseItem(_) => 100,
+            Action::Descend => 100,
+            Action::Ascend => 100,
+            Action::CastSpell(..) => 100,
+            Action::RangedAttack(_) => 100,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        actor::{Actor, CharacterStats},
+        attributes::Attributes,
+        config::Config,
+        map_generator::GeneratorKind,
+    };
+
+    /// A game with the player on their turn and a monster of the given kind standing next to them.
+    fn game_with_adjacent_monster(kind_id: &str, seed: u64) -> (GameData, usize) {
+        let mut game_data = GameData::new(Config { generator: None, seed: Some(seed) });
+        game_data.reseed();
+        game_data.map.build_floor(1, GeneratorKind::Bsp, &mut game_data.rng);
+        let (player_position, monster_position) = game_data
+            .map
+            .get_unoccupied_floor_tiles()
+            .into_iter()
+            .find_map(|position| {
+                let neighbour = Position { x: position.x + 1, y: position.y };
+                game_data.map.get_tile(neighbour).is_some_and(|tile| tile.tile_type() == TileType::Floor).then_some((position, neighbour))
+            })
+            .unwrap();
+
+        let stats = CharacterStats::new("Tester".to_string(), "warrior".to_string(), "player".to_string(), Attributes::average());
+        game_data.actors.add_player_actor(Actor::new("human".to_string(), Some(stats), player_position));
+        game_data.map.set_actor(player_position, 0);
+        game_data.actors.next_turn();
+        let monster_id = game_data.actors.add_actor(Actor::new(kind_id.to_string(), None, monster_position));
+        game_data.map.set_actor(monster_position, monster_id);
+        game_data.update_visibility();
+        (game_data, monster_id)
+    }
+
+    fn logged(game_data: &GameData, text: &str) -> bool {
+        game_data.messages.messages().iter().any(|message| message.display().contains(text))
+    }
+
+    #[test]
+    fn attacking_a_neutral_faction_turns_it_hostile() {
+        let (mut game_data, spider_id) = game_with_adjacent_monster("cave_spider", 1);
+        let spider = game_data.actors.get_actor(spider_id).unwrap();
+        assert!(!spider.is_hostile_towards(game_data.actors.get_player_actor(), &game_data.factions));
+
+        Playing::new().process_action(0, Action::MeleeAttack(spider_id), &mut game_data);
+
+        assert_eq!(game_data.factions.relation("vermin", "player"), Relation::Hostile);
+        assert_eq!(game_data.factions.relation("player", "vermin"), Relation::Hostile);
+        assert_eq!(game_data.factions.relation("goblin", "vermin"), Relation::Neutral);
+        assert!(logged(&game_data, "The vermin turn hostile!"));
+    }
+
+    #[test]
+    fn unaware_targets_take_sneak_attack_damage() {
+        let mut hits = 0;
+        for seed in 0..20 {
+            // the same swing against a goblin that has heard something and one that has not
+            let (mut unaware_game, goblin_id) = game_with_adjacent_monster("goblin", seed);
+            let (mut wary_game, _) = game_with_adjacent_monster("goblin", seed);
+            let goblin = wary_game.actors.get_actor_mut(goblin_id).unwrap();
+            goblin.hear_noise(goblin.position(), 10);
+            assert_eq!(unaware_game.actors.get_actor(goblin_id).unwrap().awareness(), Awareness::Unaware);
+
+            Playing::new().process_action(0, Action::MeleeAttack(goblin_id), &mut unaware_game);
+            Playing::new().process_action(0, Action::MeleeAttack(goblin_id), &mut wary_game);
+
+            let damage_taken = |game_data: &GameData| {
+                let goblin = game_data.actors.get_actor(goblin_id).unwrap();
+                goblin.max_health() - goblin.health()
+            };
+            assert_eq!(damage_taken(&unaware_game), damage_taken(&wary_game) * SNEAK_ATTACK_MULTIPLIER);
+            if damage_taken(&wary_game) > 0 {
+                hits += 1;
+                assert!(logged(&unaware_game, "You catch the Goblin unawares!"));
+                assert!(!logged(&wary_game, "unawares"));
+            }
         }
+        assert!(hits > 0);
     }
 }
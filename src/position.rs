@@ -1,4 +1,6 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Position {
     pub x: i64,
     pub y: i64,
@@ -25,9 +27,60 @@ impl Position {
         ]
     }
 
+    /// Distance in moves when diagonal steps cost the same as straight ones.
+    pub fn chebyshev_distance(&self, other: Position) -> i64 {
+        (self.x - other.x).abs().max((self.y - other.y).abs())
+    }
+
+    /// The tiles on a Bresenham line from here to `other`, excluding this position and including `other`.
+    pub fn line_to(&self, other: Position) -> Vec<Position> {
+        let dx = (other.x - self.x).abs();
+        let dy = -(other.y - self.y).abs();
+        let step_x = if self.x < other.x { 1 } else { -1 };
+        let step_y = if self.y < other.y { 1 } else { -1 };
+
+        let mut line = Vec::new();
+        let mut current = *self;
+        let mut error = dx + dy;
+        while current != other {
+            let doubled_error = 2 * error;
+            if doubled_error >= dy {
+                error += dy;
+                current.x += step_x;
+            }
+            if doubled_error <= dx {
+                error += dx;
+                current.y += step_y;
+            }
+            line.push(current);
+        }
+        line
+    }
+
     pub fn is_adjacent(&self, other: Position) -> bool {
         (self.x - other.x).abs() <= 1 && (self.y - other.y).abs() <= 1 && *self != other
     }
+
+    /// The rough compass direction of another position, such as "north-east".
+    pub fn direction_to(&self, other: Position) -> &'static str {
+        let (dx, dy) = (other.x - self.x, other.y - self.y);
+        // a step counts along an axis once it is at least half the step along the other
+        let east = dx * 2 > dy.abs();
+        let west = -dx * 2 > dy.abs();
+        let south = dy * 2 > dx.abs();
+        let north = -dy * 2 > dx.abs();
+        match (north, south, east, west) {
+            (true, _, true, _) => "north-east",
+            (true, _, _, true) => "north-west",
+            (_, true, true, _) => "south-east",
+            (_, true, _, true) => "south-west",
+            (true, _, _, _) => "north",
+            (_, true, _, _) => "south",
+            (_, _, true, _) => "east",
+            (_, _, _, true) => "west",
+            _ => "nearby",
+        }
+    }
 }
 
 impl std::ops::Add for Position {
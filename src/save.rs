@@ -0,0 +1,96 @@
+use crate::{GameData, actor_manager::ActorManager, faction::FactionRelations, level::Level, map_manager::MapManager, message_log::MessageLog};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Bump whenever a saved type changes shape so stale saves are rejected instead of misread.
+const SAVE_VERSION: u32 = 15;
+const SAVE_PATH: &str = "savegame.yaml";
+
+#[derive(Serialize)]
+struct SaveFileRef<'a> {
+    version: u32,
+    seed: u64,
+    rng: &'a ChaCha12Rng,
+    depth: usize,
+    map: &'a MapManager,
+    actors: &'a ActorManager,
+    levels: &'a HashMap<usize, Level>,
+    factions: &'a FactionRelations,
+    turns: u64,
+    kills: u32,
+    messages: &'a MessageLog,
+}
+
+#[derive(Deserialize)]
+struct SaveFile {
+    seed: u64,
+    rng: ChaCha12Rng,
+    depth: usize,
+    map: MapManager,
+    actors: ActorManager,
+    levels: HashMap<usize, Level>,
+    factions: FactionRelations,
+    turns: u64,
+    kills: u32,
+    messages: MessageLog,
+}
+
+/// Read on its own first so a save from another version is reported rather than failing to parse.
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+pub fn save_game(game_data: &GameData) -> Result<(), String> {
+    // the rng state is saved as it stands, so saving never changes how the rest of the run unfolds
+    let save_file = SaveFileRef {
+        version: SAVE_VERSION,
+        seed: game_data.seed,
+        rng: &game_data.rng,
+        depth: game_data.depth,
+        map: &game_data.map,
+        actors: &game_data.actors,
+        levels: &game_data.levels,
+        factions: &game_data.factions,
+        turns: game_data.turns,
+        kills: game_data.kills,
+        messages: &game_data.messages,
+    };
+
+    let content = serde_yaml::to_string(&save_file).map_err(|err| format!("Failed to serialize game: {}", err))?;
+    std::fs::write(SAVE_PATH, content).map_err(|err| format!("Failed to write save file: {}", err))
+}
+
+/// Removes the save once the character it belongs to has died.
+pub fn delete_save() {
+    let _ = std::fs::remove_file(SAVE_PATH);
+}
+
+pub fn load_game(game_data: &mut GameData) -> Result<(), String> {
+    let content = match std::fs::read_to_string(SAVE_PATH) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Err("No saved game found".to_string()),
+        Err(err) => return Err(format!("Failed to read save file: {}", err)),
+    };
+
+    let header: SaveHeader = serde_yaml::from_str(&content).map_err(|err| format!("Save file is corrupt: {}", err))?;
+    if header.version != SAVE_VERSION {
+        return Err(format!("Save file version {} is not supported, expected version {}", header.version, SAVE_VERSION));
+    }
+
+    let save_file: SaveFile = serde_yaml::from_str(&content).map_err(|err| format!("Save file is corrupt: {}", err))?;
+
+    game_data.seed = save_file.seed;
+    game_data.rng = save_file.rng;
+    game_data.depth = save_file.depth;
+    game_data.map = save_file.map;
+    game_data.actors = save_file.actors;
+    game_data.levels = save_file.levels;
+    game_data.factions = save_file.factions;
+    game_data.turns = save_file.turns;
+    game_data.kills = save_file.kills;
+    game_data.messages = save_file.messages;
+    game_data.cause_of_death = None;
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use crate::{map_manager::MapManager, position::Position};
 
-const VISION_RADIUS: i64 = 50;
+/// How far actors can see when nothing is impairing their sight.
+pub const VISION_RADIUS: i64 = 50;
 
 #[derive(Clone, Copy)]
 enum Direction {
@@ -18,23 +19,23 @@ enum Access {
 }
 
 impl MapManager {
-    /// Takes an origin position and returns a Vec<Position> of tiles that are visible from the origin.
-    pub fn shadowcast(&self, origin: Position) -> Vec<Position> {
+    /// Takes an origin position and returns a Vec<Position> of tiles that are visible from the origin, up to `radius` rows away.
+    pub fn shadowcast(&self, origin: Position, radius: i64) -> Vec<Position> {
         let mut visible_tiles: Vec<Position> = Vec::new();
 
         // Origin is always visible
-        visible_tiles.push(origin.clone());
+        visible_tiles.push(origin);
 
         for direction in [Direction::North, Direction::South, Direction::East, Direction::West] {
             let first_row = Row::new(1, -1.0, 1.0);
-            self.scan(first_row, origin, direction, &mut visible_tiles);
+            self.scan(first_row, origin, direction, radius, &mut visible_tiles);
         }
 
         visible_tiles
     }
 
-    fn scan(&self, mut row: Row, origin: Position, direction: Direction, visible_tiles: &mut Vec<Position>) {
-        if row.depth > VISION_RADIUS {
+    fn scan(&self, mut row: Row, origin: Position, direction: Direction, radius: i64, visible_tiles: &mut Vec<Position>) {
+        if row.depth > radius {
             return;
         }
 
@@ -46,7 +47,7 @@ impl MapManager {
             // check if the position is impassible
             let mut tile_access = Access::Passible;
             match self.get_tile(tile_position) {
-                Some(ref tile) => {
+                Some(tile) => {
                     if tile.blocks_vision() {
                         tile_access = Access::Impassible;
                     }
@@ -68,14 +69,14 @@ impl MapManager {
             if prev_tile_access == Access::Passible && tile_access == Access::Impassible {
                 let mut next_row = row.next();
                 next_row.end_slope = slope(row.depth, col);
-                self.scan(next_row, origin, direction, visible_tiles);
+                self.scan(next_row, origin, direction, radius, visible_tiles);
             }
 
             prev_tile_access = tile_access
         }
 
         if prev_tile_access == Access::Passible {
-            self.scan(row.next(), origin, direction, visible_tiles);
+            self.scan(row.next(), origin, direction, radius, visible_tiles);
         }
     }
 }
@@ -0,0 +1,66 @@
+use crate::{
+    actor::Actor,
+    actor_manager::ActorManager,
+    assets::{ASSETS, ActorKind},
+    map_manager::MapManager,
+    position::Position,
+    shadowcast::VISION_RADIUS,
+};
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand_chacha::ChaCha12Rng;
+
+/// Closest a monster may be placed to where the player arrives.
+const MIN_SPAWN_DISTANCE: i64 = 10;
+
+/// Populates a freshly built floor with monsters, more of them the deeper it is, keeping them
+/// away from and out of sight of where the player arrives so nothing attacks on the first turn.
+pub fn spawn_monsters(actors: &mut ActorManager, map: &mut MapManager, depth: usize, player_start: Position, rng: &mut ChaCha12Rng) {
+    let spawnable_kinds: Vec<&ActorKind> = ASSETS.actor_kinds.iter().filter(|kind| kind.spawnable).collect();
+    if spawnable_kinds.is_empty() {
+        return;
+    }
+
+    let seen_from_start = map.shadowcast(player_start, VISION_RADIUS);
+    let mut floor_positions: Vec<Position> = map.get_unoccupied_floor_tiles().into_iter().filter(|position| position.chebyshev_distance(player_start) >= MIN_SPAWN_DISTANCE && !seen_from_start.contains(position)).collect();
+    floor_positions.shuffle(rng);
+
+    let monster_count = (4 + depth * 2).min(floor_positions.len());
+    for position in floor_positions.into_iter().take(monster_count) {
+        let kind = spawnable_kinds.choose(rng).unwrap();
+        let actor_id = actors.add_actor(Actor::new(kind.id.clone(), None, position));
+        map.set_actor(position, actor_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{consts::DUNGEON_SIZE, map_generator::GeneratorKind};
+    use rand::SeedableRng;
+
+    #[test]
+    fn monsters_spawn_away_from_and_out_of_sight_of_the_player() {
+        for seed in 0..10 {
+            let mut rng = ChaCha12Rng::seed_from_u64(seed);
+            let mut map = MapManager::new();
+            let mut actors = ActorManager::new();
+            map.build_floor(3, GeneratorKind::for_depth(seed as usize + 1), &mut rng);
+            let player_start = *map.get_unoccupied_floor_tiles().choose(&mut rng).unwrap();
+            spawn_monsters(&mut actors, &mut map, 3, player_start, &mut rng);
+
+            let seen_from_start = map.shadowcast(player_start, VISION_RADIUS);
+            let mut spawned = 0;
+            for y in 0..DUNGEON_SIZE as i64 {
+                for x in 0..DUNGEON_SIZE as i64 {
+                    let position = Position { x, y };
+                    if map.get_tile(position).unwrap().actor_id().is_some() {
+                        spawned += 1;
+                        assert!(position.chebyshev_distance(player_start) >= MIN_SPAWN_DISTANCE, "monster spawned too close with seed {}", seed);
+                        assert!(!seen_from_start.contains(&position), "monster spawned in view with seed {}", seed);
+                    }
+                }
+            }
+            assert!(spawned > 0);
+        }
+    }
+}
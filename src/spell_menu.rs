@@ -0,0 +1,77 @@
+use crate::{
+    GameData, GameState,
+    assets::SpellTarget,
+    playing::{Action, Playing},
+    targeting::{Targeting, TargetingPurpose},
+};
+use crossterm::{
+    QueueableCommand,
+    cursor::MoveTo,
+    event::KeyCode,
+    style::{Color, Print, PrintStyledContent, Stylize, style},
+    terminal::{Clear, ClearType},
+};
+use std::io::Write;
+
+/// Lists the spells the player knows so one can be chosen by letter and cast.
+pub struct SpellMenu;
+
+impl SpellMenu {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn update(self, game_data: &mut GameData) -> GameState {
+        let player = game_data.actors.get_player_actor();
+        let spells = player.known_spells();
+
+        match game_data.input.last_key() {
+            KeyCode::Esc | KeyCode::Char('z') => GameState::Playing(Playing::new()),
+            KeyCode::Char(c @ 'a'..='z') if ((c as u8 - b'a') as usize) < spells.len() => {
+                let spell = spells[(c as u8 - b'a') as usize];
+                if player.mana() < spell.mana_cost {
+                    game_data.log(format!("You do not have enough mana to cast {}.", spell.name));
+                    return GameState::Playing(Playing::new());
+                }
+
+                match spell.target {
+                    SpellTarget::Caster => {
+                        let position = player.position();
+                        Playing::new().take_player_turn(Action::CastSpell(spell.id.clone(), position), game_data)
+                    }
+                    SpellTarget::Other => Targeting::start(TargetingPurpose::Spell(spell.id.clone()), game_data),
+                }
+            }
+            _ => GameState::SpellMenu(self),
+        }
+    }
+
+    pub fn draw(&self, game_data: &GameData) -> std::io::Result<()> {
+        let mut stdout = std::io::stdout();
+        stdout.queue(Clear(ClearType::All))?;
+
+        let player = game_data.actors.get_player_actor();
+        stdout.queue(MoveTo(0, 0))?;
+        stdout.queue(PrintStyledContent(style(format!("Spells (mana {}/{})", player.mana(), player.max_mana())).with(Color::Yellow)))?;
+
+        for (i, spell) in player.known_spells().iter().enumerate() {
+            let letter = (b'a' + i as u8) as char;
+            let color = if player.mana() >= spell.mana_cost { Color::White } else { Color::DarkGrey };
+            let range = match spell.target {
+                SpellTarget::Caster => "self".to_string(),
+                SpellTarget::Other => format!("range {}", spell.range),
+            };
+            stdout.queue(MoveTo(2, 2 + i as u16 * 2))?;
+            stdout.queue(PrintStyledContent(style(format!("{}) {:<16} {:>3} mana  {}", letter, spell.name, spell.mana_cost, range)).with(color)))?;
+            stdout.queue(MoveTo(5, 3 + i as u16 * 2))?;
+            stdout.queue(PrintStyledContent(style(&spell.description).with(Color::DarkGrey)))?;
+        }
+
+        let (_, height) = crossterm::terminal::size()?;
+        stdout.queue(MoveTo(0, height.saturating_sub(2)))?;
+        stdout.queue(Print("a-z cast a spell, Esc close"))?;
+
+        stdout.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+use crate::{
+    assets::{ASSETS, Stacking, StatusEffectKind},
+    attributes::Attributes,
+};
+use serde::{Deserialize, Serialize};
+
+/// Game time in action points that makes up one turn, for durations and per turn effects.
+pub const TURN_LENGTH: u32 = 100;
+
+/// An active status effect on an actor.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StatusEffect {
+    kind_id: String,
+    magnitude: i32,
+    /// Action points left before the effect wears off.
+    remaining: u32,
+    /// Action points since the effect last applied its per turn change.
+    elapsed: u32,
+}
+
+impl StatusEffect {
+    pub fn new(kind_id: String, magnitude: i32, duration_turns: i32) -> Self {
+        Self {
+            kind_id,
+            magnitude,
+            remaining: duration_turns.max(1) as u32 * TURN_LENGTH,
+            elapsed: 0,
+        }
+    }
+
+    pub fn kind(&self) -> &'static StatusEffectKind {
+        ASSETS.status_effect_kinds.iter().find(|kind| kind.id == self.kind_id).unwrap()
+    }
+
+    pub fn kind_id(&self) -> &str {
+        &self.kind_id
+    }
+
+    pub fn magnitude(&self) -> i32 {
+        self.magnitude
+    }
+
+    /// Whole turns left, rounded up.
+    pub fn remaining_turns(&self) -> u32 {
+        self.remaining.div_ceil(TURN_LENGTH)
+    }
+
+    /// Combines a new application of the same effect into this one according to the kind's stacking rule.
+    pub fn stack(&mut self, other: StatusEffect) {
+        match self.kind().stacking {
+            Stacking::Refresh => {
+                self.magnitude = self.magnitude.max(other.magnitude);
+                self.remaining = self.remaining.max(other.remaining);
+            }
+            Stacking::Extend => {
+                self.magnitude = self.magnitude.max(other.magnitude);
+                self.remaining += other.remaining;
+            }
+            Stacking::Intensify => {
+                self.magnitude += other.magnitude;
+                self.remaining = self.remaining.max(other.remaining);
+            }
+        }
+    }
+
+    /// Advances the effect by some game time, returning the health change from any whole turns that passed.
+    pub fn advance(&mut self, action_points: u32) -> i32 {
+        let action_points = action_points.min(self.remaining);
+        self.remaining -= action_points;
+        self.elapsed += action_points;
+
+        let turns = self.elapsed / TURN_LENGTH;
+        self.elapsed %= TURN_LENGTH;
+        turns as i32 * self.kind().health_per_turn * self.magnitude
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining == 0
+    }
+}
+
+/// The combined modifiers of every status effect on an actor, the one place stats look to for them.
+#[derive(Default)]
+pub struct StatModifiers {
+    pub attributes: Attributes,
+    pub speed_percent: i32,
+    pub defense: i32,
+    pub confused: bool,
+    pub blind: bool,
+}
+
+impl StatModifiers {
+    pub fn from_effects(effects: &[StatusEffect]) -> Self {
+        let mut modifiers = Self::default();
+        for effect in effects {
+            let kind = effect.kind();
+            let magnitude = effect.magnitude;
+            modifiers.attributes = modifiers.attributes + kind.attributes * magnitude;
+            modifiers.speed_percent += kind.speed_percent * magnitude;
+            modifiers.defense += kind.defense * magnitude;
+            modifiers.confused |= kind.confuses;
+            modifiers.blind |= kind.blinds;
+        }
+        modifiers
+    }
+}
+
+/// What happened to an actor's status effects as game time passed during their turn.
+#[derive(Default)]
+pub struct StatusTick {
+    /// Health lost or gained from effects, by effect, for the log.
+    pub health_changes: Vec<(&'static StatusEffectKind, i32)>,
+    pub expired: Vec<&'static StatusEffectKind>,
+    /// The effect that dealt the killing damage, if the actor died.
+    pub killed_by: Option<&'static StatusEffectKind>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // poison intensifies, haste extends and regeneration uses the default of refreshing
+    fn stacked(kind_id: &str, first: (i32, i32), second: (i32, i32)) -> StatusEffect {
+        let mut effect = StatusEffect::new(kind_id.to_string(), first.0, first.1);
+        effect.stack(StatusEffect::new(kind_id.to_string(), second.0, second.1));
+        effect
+    }
+
+    #[test]
+    fn refresh_keeps_the_strongest_and_longest() {
+        let effect = stacked("regeneration", (2, 3), (1, 5));
+        assert_eq!(effect.magnitude(), 2);
+        assert_eq!(effect.remaining_turns(), 5);
+
+        let effect = stacked("regeneration", (1, 5), (2, 3));
+        assert_eq!(effect.magnitude(), 2);
+        assert_eq!(effect.remaining_turns(), 5);
+    }
+
+    #[test]
+    fn extend_adds_the_durations() {
+        let effect = stacked("haste", (1, 3), (2, 4));
+        assert_eq!(effect.magnitude(), 2);
+        assert_eq!(effect.remaining_turns(), 7);
+    }
+
+    #[test]
+    fn intensify_adds_the_magnitudes() {
+        let effect = stacked("poison", (1, 3), (2, 4));
+        assert_eq!(effect.magnitude(), 3);
+        assert_eq!(effect.remaining_turns(), 4);
+    }
+
+    #[test]
+    fn advance_applies_whole_turns_only() {
+        let mut effect = StatusEffect::new("poison".to_string(), 2, 3);
+        let per_turn = effect.kind().health_per_turn * 2;
+        assert_eq!(effect.advance(TURN_LENGTH / 2), 0);
+        assert_eq!(effect.advance(TURN_LENGTH), per_turn);
+        // time past the end is ignored, leaving the two turns still to run
+        assert_eq!(effect.advance(TURN_LENGTH * 5), per_turn * 2);
+        assert!(effect.is_expired());
+    }
+}
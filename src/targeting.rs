@@ -0,0 +1,164 @@
+use crate::{
+    GameData, GameState,
+    assets::{ASSETS, item_name},
+    playing::{self, Action, Playing},
+    position::Position,
+};
+use crossterm::{
+    QueueableCommand,
+    cursor::MoveTo,
+    event::KeyCode,
+    style::{Color, PrintStyledContent, Stylize, style},
+};
+use std::io::Write;
+
+/// What the chosen target will be used for.
+pub enum TargetingPurpose {
+    Spell(String),
+    /// Shooting the wielded ranged weapon.
+    Fire,
+    /// Only describing what is under the cursor.
+    Look,
+}
+
+/// A cursor over the seen parts of the map for picking a target or looking around, starting on the nearest visible hostile.
+/// Looking starts on the player instead.
+pub struct Targeting {
+    purpose: TargetingPurpose,
+    cursor: Position,
+}
+
+impl Targeting {
+    pub fn start(purpose: TargetingPurpose, game_data: &mut GameData) -> GameState {
+        let player_position = game_data.actors.get_player_actor().position();
+        let cursor = match purpose {
+            TargetingPurpose::Look => player_position,
+            _ => visible_hostiles(game_data).first().copied().unwrap_or(player_position),
+        };
+        GameState::Targeting(Self { purpose, cursor })
+    }
+
+    pub fn update(mut self, game_data: &mut GameData) -> GameState {
+        let offset = match game_data.input.last_key() {
+            KeyCode::Esc => return GameState::Playing(Playing::new()),
+            KeyCode::Enter => return self.confirm(game_data),
+            KeyCode::Tab => {
+                let hostiles = visible_hostiles(game_data);
+                if !hostiles.is_empty() {
+                    let next = hostiles.iter().position(|position| *position == self.cursor).map_or(0, |index| (index + 1) % hostiles.len());
+                    self.cursor = hostiles[next];
+                }
+                None
+            }
+            KeyCode::Char('8') => Some(Position { x: 0, y: -1 }),
+            KeyCode::Char('9') => Some(Position { x: 1, y: -1 }),
+            KeyCode::Char('6') => Some(Position { x: 1, y: 0 }),
+            KeyCode::Char('3') => Some(Position { x: 1, y: 1 }),
+            KeyCode::Char('2') => Some(Position { x: 0, y: 1 }),
+            KeyCode::Char('1') => Some(Position { x: -1, y: 1 }),
+            KeyCode::Char('4') => Some(Position { x: -1, y: 0 }),
+            KeyCode::Char('7') => Some(Position { x: -1, y: -1 }),
+            _ => None,
+        };
+
+        if let Some(offset) = offset
+            && game_data.map.get_tile(self.cursor + offset).is_some_and(|tile| tile.is_seen())
+        {
+            self.cursor = self.cursor + offset;
+        }
+        GameState::Targeting(self)
+    }
+
+    /// Acts on the target under the cursor if it is valid, otherwise explains why not and keeps targeting.
+    fn confirm(self, game_data: &mut GameData) -> GameState {
+        let player = game_data.actors.get_player_actor();
+        let player_position = player.position();
+        let (action, range) = match &self.purpose {
+            TargetingPurpose::Spell(spell_id) => {
+                let spell = ASSETS.spell_kinds.iter().find(|spell| &spell.id == spell_id).unwrap();
+                (Action::CastSpell(spell_id.clone(), self.cursor), spell.range)
+            }
+            TargetingPurpose::Fire => {
+                let range = player.ranged_attack().map_or(0, |ranged_attack| ranged_attack.range);
+                (Action::RangedAttack(self.cursor), range)
+            }
+            TargetingPurpose::Look => return GameState::Playing(Playing::new()),
+        };
+
+        let in_view = game_data.map.get_tile(self.cursor).is_some_and(|tile| tile.is_visible());
+        if self.cursor == player_position {
+            game_data.log("You cannot target yourself.".to_string());
+            return GameState::Targeting(self);
+        }
+        if !in_view || player_position.chebyshev_distance(self.cursor) > range {
+            game_data.log("That is out of range.".to_string());
+            return GameState::Targeting(self);
+        }
+        Playing::new().take_player_turn(action, game_data)
+    }
+
+    pub fn draw(&self, game_data: &GameData) -> std::io::Result<()> {
+        Playing::new().draw(game_data)?;
+
+        let mut stdout = std::io::stdout();
+        if let Some((x, y)) = playing::screen_position(game_data, self.cursor)?
+            && let Some((ch, color)) = playing::map_glyph(game_data, self.cursor)
+        {
+            stdout.queue(MoveTo(x, y))?;
+            stdout.queue(PrintStyledContent(style(ch).with(color).on(Color::DarkYellow)))?;
+        }
+
+        let prompt = match &self.purpose {
+            TargetingPurpose::Spell(spell_id) => {
+                let spell = ASSETS.spell_kinds.iter().find(|spell| &spell.id == spell_id).unwrap();
+                format!("Cast {} where? (move cursor, Tab next target, Enter cast, Esc cancel)", spell.name)
+            }
+            TargetingPurpose::Fire => "Fire where? (move cursor, Tab next target, Enter fire, Esc cancel)".to_string(),
+            TargetingPurpose::Look => "Looking (move cursor, Tab next hostile, Esc done)".to_string(),
+        };
+        playing::draw_prompt(&mut stdout, &[prompt, self.describe(game_data)])?;
+
+        stdout.flush()?;
+        Ok(())
+    }
+
+    /// What the player knows about the tile under the cursor, the actor there only while it is in view.
+    fn describe(&self, game_data: &GameData) -> String {
+        let Some(tile) = game_data.map.get_tile(self.cursor) else {
+            return String::new();
+        };
+        let mut description = if tile.is_visible() { format!("You see {}", tile.tile_type().name()) } else { format!("You remember {}", tile.tile_type().name()) };
+
+        if tile.is_visible()
+            && let Some(actor_id) = tile.actor_id()
+            && let Some(actor) = game_data.actors.get_actor(actor_id)
+        {
+            let name = if actor_id == 0 { "yourself".to_string() } else { actor.indefinite_name() };
+            match actor.ai_state_description() {
+                Some(state) => description.push_str(&format!(", {} ({}, {})", name, actor.health_description(), state)),
+                None => description.push_str(&format!(", {} ({})", name, actor.health_description())),
+            }
+        }
+
+        let items: Vec<String> = tile.items().items().iter().map(|item| if item.quantity > 1 { format!("{} x{}", item_name(&item.item_id), item.quantity) } else { item_name(&item.item_id) }).collect();
+        if !items.is_empty() {
+            description.push_str(&format!(", {}", items.join(", ")));
+        }
+        description + "."
+    }
+}
+
+/// Positions of the hostile actors the player can see, nearest first.
+fn visible_hostiles(game_data: &GameData) -> Vec<Position> {
+    let player = game_data.actors.get_player_actor();
+    let mut positions: Vec<Position> = game_data
+        .map
+        .visible_actor_ids()
+        .into_iter()
+        .filter_map(|actor_id| game_data.actors.get_actor(actor_id))
+        .filter(|actor| player.is_hostile_towards(actor, &game_data.factions))
+        .map(|actor| actor.position())
+        .collect();
+    positions.sort_by_key(|position| position.chebyshev_distance(player.position()));
+    positions
+}